@@ -1,16 +1,68 @@
-use bevy::input::system::exit_on_esc_system;
+use std::ops::RangeInclusive;
+
+use bevy::diagnostic::{Diagnostic, DiagnosticId, Diagnostics};
 use bevy::prelude::*;
-use rand::{thread_rng, Rng};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use wasm_bindgen::prelude::*;
 
-use shared::pan_orbit_camera::{pan_orbit_camera, PanOrbitCamera};
+use shared::art_app::{ArtLight, CameraRig};
+use shared::art_piece::{despawn_all, AddArtPiece, ArtPiece};
+use shared::diagnostics_overlay::RegisterDiagnostic;
+use shared::pan_orbit_camera::auto_rotate::AutoRotate;
+use shared::pan_orbit_camera::constraints::{Bounds, PanOrbitConstraints};
+use shared::pan_orbit_camera::{PanOrbitCamera, PanOrbitSettings};
+use shared::params::{Params, RegisterParams};
+use shared::params_file::LoadParamsFile;
+use shared::seeded_rng::{RngStream, SeededRng};
+use shared::sim_clock::SimClock;
+use shared::ArtAppPlugin;
+
+/// The flock was tuned stepping once a frame at 60fps, so [`BoidsParams`] are per 1/60th of a second
+const STEPS_PER_SECOND: f32 = 60.0;
+
+/// Loaded from `assets/boids.params.ron`
+#[derive(Clone, Debug, Reflect, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BoidsParams {
+    /// How many boids are in the grid, changing it adds or removes the newest ones
+    pub count: u32,
+    pub max_speed: f32,
+    /// How strongly each boid steers towards the center of the flock
+    pub coherence: f32,
+    /// Boids closer than this steer away from each other
+    pub personal_space: f32,
+    /// How strongly each boid matches the flock's velocity
+    pub velocity_factor: f32,
+}
+
+impl Default for BoidsParams {
+    fn default() -> Self {
+        BoidsParams {
+            count: 512,
+            max_speed: 1.0,
+            coherence: 0.01,
+            personal_space: 2.,
+            velocity_factor: 0.01,
+        }
+    }
+}
 
-const MAX_SPEED: f32 = 1.0;
+impl Params for BoidsParams {
+    const NAME: &'static str = "Boids";
 
-const COHERENCE: f32 = 0.01;
-const PERSONAL_SPACE: f32 = 2.;
-const VELOCITY_FACTOR: f32 = 0.01;
+    fn range(field: &str) -> Option<RangeInclusive<f64>> {
+        match field {
+            "count" => Some(1.0..=5000.0),
+            "max_speed" => Some(0.0..=5.0),
+            "coherence" => Some(0.0..=0.1),
+            "personal_space" => Some(0.0..=10.0),
+            "velocity_factor" => Some(0.0..=0.1),
+            _ => None,
+        }
+    }
+}
 
 #[derive(Component, Default)]
 struct Boid;
@@ -18,23 +70,102 @@ struct Boid;
 #[derive(Component, Default)]
 struct Velocity(Vec3);
 
+/// A boid from the grid, by its place in it, so [`BoidsParams::count`] can grow and shrink the grid
+#[derive(Component)]
+struct GridBoid(u32);
+
+/// Every boid looks the same, so they share these while the piece is showing
+struct BoidAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+}
+
+#[derive(Component)]
+struct BoidsScene;
+
 #[wasm_bindgen(start)]
 pub fn run() {
     App::new()
-        .insert_resource(Msaa { samples: 4 })
-        .add_plugins(DefaultPlugins)
-        .add_startup_system(setup)
-        .add_system(emergent_system)
-        .add_system(move_system)
-        .add_system(pan_orbit_camera)
-        .add_system(exit_on_esc_system)
+        .add_plugin(ArtAppPlugin::piece::<BoidsPlugin>())
+        .add_art_piece(BoidsPlugin)
         .run();
 }
 
+pub struct BoidsPlugin;
+
+impl BoidsPlugin {
+    pub const BOID_COUNT: DiagnosticId =
+        DiagnosticId::from_u128(311833465973812932344104978111045957317);
+    /// Every boid checks the distance to every other boid, so this grows with the square of the flock
+    pub const NEIGHBOR_CHECKS: DiagnosticId =
+        DiagnosticId::from_u128(181868214723884455599463218296511725495);
+}
+
+impl ArtPiece for BoidsPlugin {
+    const NAME: &'static str = "boids";
+    const TITLE: &'static str = "Boids";
+
+    fn camera() -> CameraRig {
+        CameraRig {
+            pan_orbit: PanOrbitCamera {
+                radius: 50.,
+                auto_rotate: Some(AutoRotate::default()),
+                ..Default::default()
+            },
+            settings: PanOrbitSettings {
+                min_radius: 2.,
+                max_radius: 250.,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    // the sun lights the flock
+    fn light() -> Option<ArtLight> {
+        None
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, SystemLabel)]
+enum BoidsSystem {
+    Steer,
+}
+
+impl Plugin for BoidsPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_params::<BoidsParams>()
+            .load_params_file::<BoidsParams>("boids.params.ron")
+            .register_diagnostic(Diagnostic::new(Self::BOID_COUNT, "boids", 20))
+            .register_diagnostic(Diagnostic::new(
+                Self::NEIGHBOR_CHECKS,
+                "neighbor_checks",
+                20,
+            ))
+            .add_system_set(SystemSet::on_enter(Self::state()).with_system(setup))
+            .add_system_set(
+                SystemSet::on_update(Self::state())
+                    // always in the same order so the flock moves the same way every time
+                    .with_system(resize_flock.before(BoidsSystem::Steer))
+                    .with_system(emergent_system.label(BoidsSystem::Steer))
+                    .with_system(move_system.after(BoidsSystem::Steer))
+                    .with_system(flock_camera_bounds),
+            )
+            .add_system_set(
+                SystemSet::on_exit(Self::state())
+                    .with_system(despawn_all::<BoidsScene>)
+                    .with_system(remove_boid_assets),
+            );
+    }
+}
+
 fn emergent_system(
+    clock: Res<SimClock>,
+    params: Res<BoidsParams>,
     boids: Query<Entity, With<Boid>>,
     transforms: Query<(&Transform, Entity), With<Boid>>,
     mut velocities: Query<(&mut Velocity, Entity), With<Boid>>,
+    mut diagnostics: ResMut<Diagnostics>,
 ) {
     let (center_sum, num_other_boids) = transforms.iter().fold(
         (Vec3::ZERO, -1),
@@ -49,6 +180,8 @@ fn emergent_system(
             velocity_sum + *velocity
         });
 
+    let steps = clock.delta_seconds() * STEPS_PER_SECOND;
+    let mut neighbor_checks = 0;
     for my_entity in boids.iter() {
         let my_position = transforms.get(my_entity).unwrap().0.translation;
         let my_velocity = velocities.get(my_entity).unwrap().0 .0;
@@ -58,13 +191,14 @@ fn emergent_system(
         let other_center = (center_sum - my_position) / num_other_boids as f32;
         let to_center = other_center - my_position;
 
-        velocity_delta += to_center * COHERENCE;
+        velocity_delta += to_center * params.coherence;
 
         // avoidance velocity
         let mut avoidance_vector = Vec3::ZERO;
         for (transform, entity) in transforms.iter() {
+            neighbor_checks += 1;
             let opposite_direction = my_position - transform.translation;
-            if entity != my_entity && opposite_direction.length() <= PERSONAL_SPACE {
+            if entity != my_entity && opposite_direction.length() <= params.personal_space {
                 avoidance_vector += opposite_direction;
             }
         }
@@ -75,127 +209,190 @@ fn emergent_system(
         let other_velocities = (velocity_sum - my_velocity) / num_other_boids as f32;
         let to_other_velocities = other_velocities - my_velocity;
 
-        velocity_delta += to_other_velocities * VELOCITY_FACTOR;
+        velocity_delta += to_other_velocities * params.velocity_factor;
 
         let my_velocity = &mut velocities.get_mut(my_entity).unwrap().0 .0;
-        *my_velocity += velocity_delta * 0.1;
-        *my_velocity = my_velocity.clamp_length_max(MAX_SPEED);
+        *my_velocity += velocity_delta * 0.1 * steps;
+        *my_velocity = my_velocity.clamp_length_max(params.max_speed);
     }
+
+    diagnostics.add_measurement(BoidsPlugin::BOID_COUNT, (num_other_boids + 1) as f64);
+    diagnostics.add_measurement(BoidsPlugin::NEIGHBOR_CHECKS, neighbor_checks as f64);
 }
 
-fn move_system(mut query: Query<(&mut Transform, &Velocity)>) {
+fn move_system(clock: Res<SimClock>, mut query: Query<(&mut Transform, &Velocity)>) {
+    let steps = clock.delta_seconds() * STEPS_PER_SECOND;
     for (mut position, Velocity(velocity)) in query.iter_mut() {
-        position.translation += *velocity;
+        position.translation += *velocity * steps;
         position.look_at(*velocity, Vec3::Z);
     }
 }
 
-fn setup(
+/// keep the camera focused somewhere in the flock so it's hard to lose
+fn flock_camera_bounds(
+    boids: Query<&Transform, With<Boid>>,
+    mut cameras: Query<&mut PanOrbitConstraints>,
+) {
+    let (center_sum, num_boids) = boids
+        .iter()
+        .fold((Vec3::ZERO, 0), |(center_sum, num_boids), transform| {
+            (center_sum + transform.translation, num_boids + 1)
+        });
+    if num_boids == 0 {
+        return;
+    }
+
+    let center = center_sum / num_boids as f32;
+    let radius = boids
+        .iter()
+        .map(|transform| transform.translation.distance(center))
+        .fold(0.0, f32::max);
+
+    for mut constraints in cameras.iter_mut() {
+        constraints.focus_bounds = Some(Bounds::Sphere { center, radius });
+    }
+}
+
+/// Add or remove grid boids until there are [`BoidsParams::count`], this also spawns the grid when entering the piece
+fn resize_flock(
     mut commands: Commands,
-    mut meshes: ResMut<Assets<Mesh>>,
-    mut materials: ResMut<Assets<StandardMaterial>>,
+    params: Res<BoidsParams>,
+    assets: Option<Res<BoidAssets>>,
+    seeded_rng: Res<SeededRng>,
+    mut rng: Local<Option<RngStream>>,
+    grid: Query<(Entity, &GridBoid)>,
 ) {
-    let mut rng = thread_rng();
-    // boids
-    for x in (-10..20).step_by(4) {
-        for y in (-10..20).step_by(4) {
-            // let z = 0;
-            for z in (-10..20).step_by(4) {
-                commands
-                    .spawn_bundle(PbrBundle {
-                        mesh: meshes.add(Mesh::from(shape::Icosphere {
-                            radius: 0.2,
-                            subdivisions: 1,
-                        })),
-                        material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
-                        transform: Transform::from_xyz(x as f32, y as f32, z as f32),
-                        ..Default::default()
-                    })
-                    .insert(Boid)
-                    // .insert(Boid { flock: Vec::new() })
-                    .insert(Velocity(
-                        Vec3::new(
-                            rng.gen_range(-1.0..1.0),
-                            rng.gen_range(-1.0..1.0),
-                            rng.gen_range(-1.0..1.0),
-                        )
-                        .clamp_length_max(MAX_SPEED),
-                    ));
+    let assets = match assets {
+        Some(assets) => assets,
+        None => return,
+    };
+    let count = grid.iter().count() as u32;
+    if count > params.count {
+        // the newest boids go first
+        for (entity, GridBoid(i)) in grid.iter() {
+            if *i >= params.count {
+                commands.entity(entity).despawn_recursive();
             }
         }
+        return;
+    }
+
+    // the same seed starts the same flock, every time the piece is entered
+    if count == 0 {
+        *rng = None;
+    }
+    let rng = rng.get_or_insert_with(|| seeded_rng.stream("boids::grid"));
+    // boids, in an 8x8 grid of columns that grows along x
+    for i in count..params.count {
+        let (x, y, z) = (i / 64, i / 8 % 8, i % 8);
+        commands
+            .spawn_bundle(PbrBundle {
+                mesh: assets.mesh.clone(),
+                material: assets.material.clone(),
+                transform: Transform::from_xyz(
+                    (x * 4) as f32 - 10.,
+                    (y * 4) as f32 - 10.,
+                    (z * 4) as f32 - 10.,
+                ),
+                ..Default::default()
+            })
+            .insert(Boid)
+            .insert(GridBoid(i))
+            .insert(BoidsScene)
+            .insert(Velocity(
+                Vec3::new(
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                )
+                .clamp_length_max(params.max_speed),
+            ));
     }
+}
+
+fn setup(
+    mut commands: Commands,
+    params: Res<BoidsParams>,
+    seeded_rng: Res<SeededRng>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    // the same seed starts the same flock, the grid is spawned by `resize_flock`
+    let mut rng = seeded_rng.stream("boids::setup");
+    let assets = BoidAssets {
+        mesh: meshes.add(Mesh::from(shape::Icosphere {
+            radius: 0.2,
+            subdivisions: 1,
+        })),
+        material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
+    };
 
     commands
         .spawn_bundle(PbrBundle {
-            mesh: meshes.add(Mesh::from(shape::Icosphere {
-                radius: 0.2,
-                subdivisions: 1,
-            })),
-            material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
+            mesh: assets.mesh.clone(),
+            material: assets.material.clone(),
             transform: Transform::from_xyz(1., 1., 0.),
             ..Default::default()
         })
         .insert(Boid)
+        .insert(BoidsScene)
         .insert(Velocity(
             Vec3::new(
                 rng.gen_range(-1.0..1.0),
                 rng.gen_range(-1.0..1.0),
                 rng.gen_range(-1.0..1.0),
             )
-            .clamp_length_max(MAX_SPEED),
+            .clamp_length_max(params.max_speed),
         ));
 
     commands
         .spawn_bundle(PbrBundle {
-            mesh: meshes.add(Mesh::from(shape::Icosphere {
-                radius: 0.2,
-                subdivisions: 1,
-            })),
-            material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
+            mesh: assets.mesh.clone(),
+            material: assets.material.clone(),
             transform: Transform::from_xyz(-1., -1., 0.),
             ..Default::default()
         })
         .insert(Boid)
+        .insert(BoidsScene)
         .insert(Velocity(
             Vec3::new(
                 rng.gen_range(-1.0..1.0),
                 rng.gen_range(-1.0..1.0),
                 rng.gen_range(-1.0..1.0),
             )
-            .clamp_length_max(MAX_SPEED),
+            .clamp_length_max(params.max_speed),
         ));
 
     // "sun"
-    commands.spawn_bundle(PbrBundle {
-        mesh: meshes.add(Mesh::from(shape::Icosphere {
-            radius: 1.,
-            subdivisions: 1,
-        })),
-        material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
-        transform: Transform::from_xyz(0.0, 0.5, 0.0),
-        ..Default::default()
-    });
-
-    // "sun" light
-    commands.spawn_bundle(PointLightBundle {
-        transform: Transform::from_xyz(0., 0., 0.),
-        point_light: PointLight {
-            intensity: 100000.,
-            color: Color::WHITE,
-            shadows_enabled: false,
+    commands
+        .spawn_bundle(PbrBundle {
+            mesh: meshes.add(Mesh::from(shape::Icosphere {
+                radius: 1.,
+                subdivisions: 1,
+            })),
+            material: materials.add(Color::rgb(0.8, 0.7, 0.6).into()),
+            transform: Transform::from_xyz(0.0, 0.5, 0.0),
             ..Default::default()
-        },
-        ..Default::default()
-    });
+        })
+        .insert(BoidsScene);
 
-    // camera
+    // "sun" light
     commands
-        .spawn_bundle(PerspectiveCameraBundle {
-            transform: Transform::from_xyz(-2.0, 2.5, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+        .spawn_bundle(PointLightBundle {
+            transform: Transform::from_xyz(0., 0., 0.),
+            point_light: PointLight {
+                intensity: 100000.,
+                color: Color::WHITE,
+                shadows_enabled: false,
+                ..Default::default()
+            },
             ..Default::default()
         })
-        .insert(PanOrbitCamera {
-            radius: 50.,
-            ..Default::default()
-        });
+        .insert(BoidsScene);
+
+    commands.insert_resource(assets);
+}
+
+fn remove_boid_assets(mut commands: Commands) {
+    commands.remove_resource::<BoidAssets>();
 }
@@ -40,19 +40,22 @@ fn vertex(vertex: Vertex) -> VertexOutput {
 
 
 
-struct Time {
+struct Fractal {
     time_since_startup: f32;
+    power_divisor: f32;
+    epsilon: f32;
+    max_marching_steps: u32;
+    // 0 animates the power with the time instead
+    power: f32;
 };
 
 [[group(2), binding(0)]]
-var<uniform> time: Time;
-
-let MAX_MARCHING_STEPS: u32 = 150u;
-let EPSILON: f32 = 0.001;
+var<uniform> fractal: Fractal;
 
 fn distance_estimator(point: vec3<f32>) -> f32 {
     //return length(point) - 0.15;
-    return mandelbulb_de(point, time.time_since_startup / 8.0);
+    let animated_power = fractal.time_since_startup / fractal.power_divisor;
+    return mandelbulb_de(point, select(animated_power, fractal.power, fractal.power > 0.0));
 }
 
 // TODO distance_estimator should be in module scope https://gpuweb.github.io/gpuweb/wgsl/#module-scope
@@ -60,19 +63,33 @@ fn distance_estimator(point: vec3<f32>) -> f32 {
 #import "shaders/sdf/lib.wgsl"
 
 [[stage(fragment)]]
-fn fragment(in: VertexOutput) -> [[location(0)]] vec4<f32> {
+fn fragment(in: VertexOutput, [[builtin(front_facing)]] is_front: bool) -> [[location(0)]] vec4<f32> {
+    // from outside of the cube we march in from its front faces, but from inside we can only see its back faces
+    let camera_inside = all(abs(view.world_position) < vec3<f32>(1.0));
+    if (camera_inside == is_front) {
+        discard;
+    }
+
+    var ray_position = in.ray_position;
+    var max_distance = in.max_distance;
+    if (camera_inside) {
+        // march from the camera to where the ray leaves the cube
+        ray_position = view.world_position;
+        max_distance = distance(view.world_position, in.world_position);
+    }
+
     // interpolation doesn't work across vectors like this since it would change our projection, so calculate here
     let ray = normalize(in.world_position - view.world_position);
-    let march_result = ray_march(in.ray_position, ray, MAX_MARCHING_STEPS, EPSILON, in.max_distance);
+    let march_result = ray_march(ray_position, ray, fractal.max_marching_steps, fractal.epsilon, max_distance);
 
     if (march_result.collided) {
-        let normal = estimate_normal(march_result.point, EPSILON);
+        let normal = estimate_normal(march_result.point, fractal.epsilon);
         let color = phong_lighting(
             march_result.point,
             normal,
             lights.ambient_color.xyz,
             vec3<f32>(0.0, 0.0, 1.0),
-            vec3<f32>(f32(march_result.steps) / f32(MAX_MARCHING_STEPS), 0., 0.2),
+            vec3<f32>(f32(march_result.steps) / f32(fractal.max_marching_steps), 0., 0.2),
             0.5,
             10.0
         );
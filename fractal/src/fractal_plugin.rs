@@ -1,3 +1,5 @@
+use std::ops::RangeInclusive;
+
 use bevy::core_pipeline::Transparent3d;
 use bevy::ecs::system::lifetimeless::SRes;
 use bevy::ecs::system::SystemParamItem;
@@ -19,34 +21,63 @@ use bevy::render::renderer::{RenderDevice, RenderQueue};
 use bevy::render::view::ExtractedView;
 use bevy::render::{RenderApp, RenderStage};
 
-pub struct FractalPlugin;
+use serde::{Deserialize, Serialize};
+
+use shared::params::{Params, RegisterParams};
+use shared::params_file::LoadParamsFile;
+use shared::sim_clock::SimClock;
+
+pub struct FractalMaterialPlugin;
 
 #[derive(Component)]
 pub struct FractalMaterial;
 
-impl Plugin for FractalPlugin {
-    fn build(&self, app: &mut App) {
-        let render_device = app.world.get_resource::<RenderDevice>().unwrap();
-        let size = std::mem::size_of::<f32>() as u64;
+/// Loaded from `assets/fractal.params.ron`
+#[derive(Clone, Debug, Reflect, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FractalParams {
+    /// Give up on a ray after this many steps
+    pub max_marching_steps: u32,
+    /// How close a ray has to get to count as a hit
+    pub epsilon: f32,
+    /// The mandelbulb's power animates with the clock divided by this, so bigger is slower
+    pub power_divisor: f32,
+    /// Hold the mandelbulb at this power instead of animating it, 0 animates
+    pub power: f32,
+}
+
+impl Default for FractalParams {
+    fn default() -> Self {
+        FractalParams {
+            max_marching_steps: 150,
+            epsilon: 0.001,
+            power_divisor: 8.0,
+            power: 0.0,
+        }
+    }
+}
 
-        // TODO we're multiplying by 4 here to work around https://bugzilla.mozilla.org/show_bug.cgi?id=1569926
-        // which seems to exist in some form for FF and Chrome on Mac
-        #[cfg(target_arch = "wasm32")]
-        let size = size * 4;
+impl Params for FractalParams {
+    const NAME: &'static str = "Fractal";
 
-        let time_buffer = render_device.create_buffer(&BufferDescriptor {
-            label: Some("time uniform buffer"),
-            size,
-            usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
-            mapped_at_creation: false,
-        });
+    fn range(field: &str) -> Option<RangeInclusive<f64>> {
+        match field {
+            "max_marching_steps" => Some(1.0..=500.0),
+            "epsilon" => Some(0.00001..=0.01),
+            "power_divisor" => Some(1.0..=64.0),
+            "power" => Some(0.0..=16.0),
+            _ => None,
+        }
+    }
+}
+
+impl Plugin for FractalMaterialPlugin {
+    fn build(&self, app: &mut App) {
+        app.register_params::<FractalParams>()
+            .load_params_file::<FractalParams>("fractal.params.ron");
 
         app.sub_app_mut(RenderApp)
             .add_render_command::<Transparent3d, DrawFractal>()
-            .insert_resource(TimeMeta {
-                buffer: time_buffer,
-                bind_group: None,
-            })
             .init_resource::<FractalPipeline>()
             .init_resource::<SpecializedPipelines<FractalPipeline>>()
             .add_system_to_stage(RenderStage::Extract, extract_time)
@@ -57,11 +88,16 @@ impl Plugin for FractalPlugin {
     }
 }
 
-// extract the passed time into a resource in the render world
-fn extract_time(mut commands: Commands, time: Res<Time>) {
-    commands.insert_resource(ExtractedTime {
-        seconds_since_startup: time.seconds_since_startup() as f32,
-    });
+// extract the passed time and the params into a resource in the render world,
+// the params are only there while the fractal is showing
+fn extract_time(mut commands: Commands, clock: Res<SimClock>, params: Option<Res<FractalParams>>) {
+    match params {
+        Some(params) => commands.insert_resource(ExtractedTime {
+            seconds_since_startup: clock.seconds_since_startup() as f32,
+            params: params.clone(),
+        }),
+        None => commands.remove_resource::<ExtractedTime>(),
+    }
 }
 
 // extract the `FractalMaterial` component into the render world
@@ -78,17 +114,58 @@ fn extract_fractal_material(
     commands.insert_or_spawn_batch(values);
 }
 
-// write the extracted time into the corresponding uniform buffer
+// write the extracted time and params into the corresponding uniform buffer, laid out like `Fractal` in the shader,
+// creating the buffer when the fractal starts showing and dropping it when it stops
 fn prepare_time(
-    time: Res<ExtractedTime>,
-    time_meta: ResMut<TimeMeta>,
+    mut commands: Commands,
+    time: Option<Res<ExtractedTime>>,
+    time_meta: Option<Res<TimeMeta>>,
+    render_device: Res<RenderDevice>,
     render_queue: Res<RenderQueue>,
 ) {
+    let time = match time {
+        Some(time) => time,
+        None => {
+            if time_meta.is_some() {
+                commands.remove_resource::<TimeMeta>();
+            }
+            return;
+        }
+    };
+    let buffer = match time_meta {
+        Some(time_meta) => time_meta.buffer.clone(),
+        None => {
+            let buffer = render_device.create_buffer(&BufferDescriptor {
+                label: Some("fractal uniform buffer"),
+                // at least 16 bytes keeps us clear of https://bugzilla.mozilla.org/show_bug.cgi?id=1569926
+                // which seems to exist in some form for FF and Chrome on Mac
+                size: FRACTAL_UNIFORM_SIZE,
+                usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+            commands.insert_resource(TimeMeta {
+                buffer: buffer.clone(),
+                bind_group: None,
+            });
+            buffer
+        }
+    };
+
     render_queue.write_buffer(
-        &time_meta.buffer,
+        &buffer,
         0,
-        bevy::core::cast_slice(&[time.seconds_since_startup]),
+        bevy::core::cast_slice(&[
+            time.seconds_since_startup,
+            time.params.power_divisor,
+            time.params.epsilon,
+        ]),
     );
+    render_queue.write_buffer(
+        &buffer,
+        12,
+        bevy::core::cast_slice(&[time.params.max_marching_steps.max(1)]),
+    );
+    render_queue.write_buffer(&buffer, 16, bevy::core::cast_slice(&[time.params.power]));
 }
 
 // add each entity with a mesh and a `FractalMaterial` to every view's `Transparent3d` render phase using the `FractalPipeline`
@@ -129,9 +206,13 @@ fn queue_fractal(
 // create a bind group for the time uniform buffer
 fn queue_time_bind_group(
     render_device: Res<RenderDevice>,
-    mut time_meta: ResMut<TimeMeta>,
+    time_meta: Option<ResMut<TimeMeta>>,
     pipeline: Res<FractalPipeline>,
 ) {
+    let mut time_meta = match time_meta {
+        Some(time_meta) => time_meta,
+        None => return,
+    };
     let bind_group = render_device.create_bind_group(&BindGroupDescriptor {
         label: None,
         layout: &pipeline.time_bind_group_layout,
@@ -165,7 +246,7 @@ impl FromWorld for FractalPipeline {
                     ty: BindingType::Buffer {
                         ty: BufferBindingType::Uniform,
                         has_dynamic_offset: false,
-                        min_binding_size: BufferSize::new(std::mem::size_of::<f32>() as u64),
+                        min_binding_size: BufferSize::new(FRACTAL_UNIFORM_SIZE),
                     },
                     count: None,
                 }],
@@ -188,6 +269,8 @@ impl SpecializedPipeline for FractalPipeline {
         let mut descriptor = self.mesh_pipeline.specialize(key);
         descriptor.vertex.shader = self.shader.clone();
         descriptor.fragment.as_mut().unwrap().shader = self.shader.clone();
+        // the shader picks which faces to draw so we can still see the fractal when flying inside the cube
+        descriptor.primitive.cull_mode = None;
         descriptor.layout = Some(vec![
             self.mesh_pipeline.view_layout.clone(),
             self.mesh_pipeline.mesh_layout.clone(),
@@ -197,9 +280,14 @@ impl SpecializedPipeline for FractalPipeline {
     }
 }
 
+/// time, power divisor and epsilon as f32s, the max marching steps as a u32 and the power as an f32,
+/// padded out to a multiple of 16 bytes
+const FRACTAL_UNIFORM_SIZE: u64 = 32;
+
 #[derive(Default)]
 struct ExtractedTime {
     seconds_since_startup: f32,
+    params: FractalParams,
 }
 
 struct TimeMeta {
@@ -1,24 +1,72 @@
-use bevy::input::system::exit_on_esc_system;
 use bevy::prelude::*;
 use wasm_bindgen::prelude::*;
 
-use shared::pan_orbit_camera::{pan_orbit_camera, PanOrbitCamera};
+use shared::art_app::CameraRig;
+use shared::art_piece::{despawn_all, AddArtPiece, ArtPiece};
+use shared::pan_orbit_camera::auto_rotate::AutoRotate;
+use shared::pan_orbit_camera::constraints::{KeepOut, PanOrbitConstraints};
+use shared::pan_orbit_camera::{PanOrbitCamera, PanOrbitSettings};
+use shared::ArtAppPlugin;
 
-use crate::fractal_plugin::{FractalMaterial, FractalPlugin};
+use crate::fractal_plugin::{FractalMaterial, FractalMaterialPlugin};
 
 mod fractal_plugin;
 
 #[wasm_bindgen(start)]
 pub fn run() {
     App::new()
-        .add_plugins(DefaultPlugins)
-        .add_plugin(FractalPlugin)
-        .add_startup_system(setup)
-        .add_system(pan_orbit_camera)
-        .add_system(exit_on_esc_system)
+        .add_plugin(ArtAppPlugin::piece::<FractalPlugin>())
+        .add_art_piece(FractalPlugin)
         .run();
 }
 
+pub struct FractalPlugin;
+
+impl ArtPiece for FractalPlugin {
+    const NAME: &'static str = "fractal";
+    const TITLE: &'static str = "Fractal";
+
+    fn camera() -> CameraRig {
+        CameraRig {
+            pan_orbit: PanOrbitCamera {
+                radius: 2.,
+                auto_rotate: Some(AutoRotate {
+                    bob_amplitude: 0.3,
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            constraints: PanOrbitConstraints {
+                // the fractal fills the cube, it's still drawn from inside for the fly camera,
+                // but orbiting stays outside so the camera never ends up buried in the surface
+                keep_out: Some(KeepOut::Sdf {
+                    distance: bounding_cube,
+                    margin: 0.15,
+                }),
+                ..Default::default()
+            },
+            settings: PanOrbitSettings {
+                max_radius: 10.,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+}
+
+impl Plugin for FractalPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(FractalMaterialPlugin)
+            .add_system_set(SystemSet::on_enter(Self::state()).with_system(setup))
+            .add_system_set(
+                SystemSet::on_exit(Self::state()).with_system(despawn_all::<FractalScene>),
+            );
+    }
+}
+
+#[derive(Component)]
+struct FractalScene;
+
 fn setup(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
     commands.spawn().insert_bundle((
         meshes.add(Mesh::from(shape::Cube { size: 2.0 })),
@@ -28,29 +76,12 @@ fn setup(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
         FractalMaterial,
         Visibility::default(),
         ComputedVisibility::default(),
+        FractalScene,
     ));
+}
 
-    // red point light
-    commands.spawn_bundle(PointLightBundle {
-        // transform: Transform::from_xyz(5.0, 8.0, 2.0),
-        transform: Transform::from_xyz(1.0, 2.0, 0.0),
-        point_light: PointLight {
-            intensity: 1600.0, // lumens - roughly a 100W non-halogen incandescent bulb
-            color: Color::WHITE,
-            shadows_enabled: false,
-            ..Default::default()
-        },
-        ..Default::default()
-    });
-
-    // camera
-    commands
-        .spawn_bundle(PerspectiveCameraBundle {
-            transform: Transform::from_xyz(-2.0, 2.5, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
-            ..Default::default()
-        })
-        .insert(PanOrbitCamera {
-            radius: 2.,
-            ..Default::default()
-        });
+/// signed distance to the cube the fractal is rendered on
+fn bounding_cube(point: Vec3) -> f32 {
+    let outside = point.abs() - Vec3::ONE;
+    outside.max(Vec3::ZERO).length() + outside.max_element().min(0.0)
 }
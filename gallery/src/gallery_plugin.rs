@@ -0,0 +1,153 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use shared::art_piece::{despawn_all, ArtPieces, ArtState};
+use shared::launch_config::LaunchConfig;
+
+/// A menu of every [`ArtPieces`] entry, keys to switch between them and an optional [`Slideshow`]
+pub struct GalleryPlugin;
+
+impl Plugin for GalleryPlugin {
+    fn build(&self, app: &mut App) {
+        let mut slideshow = Slideshow::default();
+        if let Some(mut config) = app.world.get_resource_mut::<LaunchConfig>() {
+            slideshow.enabled = config.parse("slideshow").unwrap_or(false);
+            if let Some(seconds) = config.parse("slideshow_seconds") {
+                slideshow.seconds = seconds;
+            }
+        }
+
+        app.init_resource::<GallerySettings>()
+            .init_resource::<ArtPieces>()
+            .insert_resource(slideshow)
+            .add_system_set(SystemSet::on_enter(ArtState::Menu).with_system(spawn_menu_camera))
+            .add_system_set(SystemSet::on_update(ArtState::Menu).with_system(gallery_menu))
+            .add_system_set(
+                SystemSet::on_exit(ArtState::Menu).with_system(despawn_all::<MenuCamera>),
+            )
+            .add_system(gallery_input)
+            .add_system(advance_slideshow);
+    }
+}
+
+pub struct GallerySettings {
+    /// Leave the piece for the menu, this also stops the slideshow
+    pub menu_key: KeyCode,
+    pub next_key: KeyCode,
+}
+
+impl Default for GallerySettings {
+    fn default() -> Self {
+        GallerySettings {
+            menu_key: KeyCode::M,
+            next_key: KeyCode::N,
+        }
+    }
+}
+
+/// Move on to the next piece every `seconds`
+pub struct Slideshow {
+    pub enabled: bool,
+    pub seconds: f32,
+    elapsed: f32,
+}
+
+impl Default for Slideshow {
+    fn default() -> Self {
+        Slideshow {
+            enabled: false,
+            seconds: 60.0,
+            elapsed: 0.0,
+        }
+    }
+}
+
+/// Nothing else draws while the menu is up, so this clears the window behind it
+#[derive(Component)]
+struct MenuCamera;
+
+fn spawn_menu_camera(mut commands: Commands) {
+    commands
+        .spawn_bundle(OrthographicCameraBundle::new_2d())
+        .insert(MenuCamera);
+}
+
+fn gallery_menu(
+    mut egui_context: ResMut<EguiContext>,
+    settings: Res<GallerySettings>,
+    pieces: Res<ArtPieces>,
+    mut slideshow: ResMut<Slideshow>,
+    mut state: ResMut<State<ArtState>>,
+) {
+    egui::Window::new("Gallery")
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .collapsible(false)
+        .resizable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            for piece in pieces.iter() {
+                if ui.button(piece.title).clicked() {
+                    show(&mut state, ArtState::Piece(piece.name));
+                }
+            }
+
+            ui.separator();
+            ui.checkbox(&mut slideshow.enabled, "Slideshow");
+            ui.add(
+                egui::Slider::new(&mut slideshow.seconds, 5.0..=600.0)
+                    .logarithmic(true)
+                    .text("seconds per piece"),
+            );
+            ui.label(format!(
+                "{:?} comes back here, {:?} skips to the next piece",
+                settings.menu_key, settings.next_key
+            ));
+        });
+}
+
+fn gallery_input(
+    input_keyboard: Res<Input<KeyCode>>,
+    settings: Res<GallerySettings>,
+    pieces: Res<ArtPieces>,
+    mut slideshow: ResMut<Slideshow>,
+    mut state: ResMut<State<ArtState>>,
+) {
+    if input_keyboard.just_pressed(settings.menu_key) {
+        slideshow.enabled = false;
+        show(&mut state, ArtState::Menu);
+    } else if input_keyboard.just_pressed(settings.next_key) {
+        if let Some(next) = pieces.next(state.current()) {
+            show(&mut state, ArtState::Piece(next.name));
+        }
+    }
+}
+
+fn advance_slideshow(
+    time: Res<Time>,
+    pieces: Res<ArtPieces>,
+    mut slideshow: ResMut<Slideshow>,
+    mut state: ResMut<State<ArtState>>,
+) {
+    // every piece gets its full time, however it was switched to
+    if !slideshow.enabled || state.is_changed() {
+        slideshow.elapsed = 0.0;
+        return;
+    }
+
+    slideshow.elapsed += time.delta_seconds();
+    // start right away from the menu
+    if *state.current() == ArtState::Menu || slideshow.elapsed >= slideshow.seconds {
+        if let Some(next) = pieces.next(state.current()) {
+            show(&mut state, ArtState::Piece(next.name));
+        }
+    }
+}
+
+/// Switch to `next`, replacing any switch already asked for this frame
+fn show(state: &mut State<ArtState>, next: ArtState) {
+    if *state.current() != next {
+        info!("Showing {:?}", next);
+        state
+            .overwrite_set(next)
+            .expect("it isn't the current state");
+    }
+}
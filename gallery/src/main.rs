@@ -0,0 +1,26 @@
+use bevy::prelude::*;
+
+use boids::BoidsPlugin;
+use fractal::FractalPlugin;
+use shared::art_piece::AddArtPiece;
+use shared::ArtAppPlugin;
+
+use crate::gallery_plugin::GalleryPlugin;
+
+mod gallery_plugin;
+
+/// Every piece in one window, starting from a menu.
+/// Launch with `--slideshow` to cycle through them, `--slideshow-seconds` sets how long each one is shown.
+fn main() {
+    App::new()
+        .add_plugin(ArtAppPlugin {
+            name: "gallery",
+            title: "Gallery",
+            asset_folders: &["../boids/assets", "../fractal/assets"],
+            ..Default::default()
+        })
+        .add_plugin(GalleryPlugin)
+        .add_art_piece(BoidsPlugin)
+        .add_art_piece(FractalPlugin)
+        .run();
+}
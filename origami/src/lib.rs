@@ -1,21 +1,57 @@
-use bevy::input::system::exit_on_esc_system;
 use bevy::prelude::*;
 use bevy::render::mesh::Indices;
 use bevy::render::render_resource::PrimitiveTopology;
-use shared::pan_orbit_camera::{pan_orbit_camera, PanOrbitCamera};
+use shared::art_app::CameraRig;
+use shared::art_piece::{despawn_all, AddArtPiece, ArtPiece};
+use shared::pan_orbit_camera::auto_rotate::AutoRotate;
+use shared::pan_orbit_camera::{PanOrbitCamera, PanOrbitSettings};
+use shared::ArtAppPlugin;
 use std::f32::consts::{PI, TAU};
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen(start)]
 pub fn run() {
     App::new()
-        .add_plugins(DefaultPlugins)
-        .add_startup_system(setup)
-        .add_system(pan_orbit_camera)
-        .add_system(exit_on_esc_system)
+        .add_plugin(ArtAppPlugin::piece::<OrigamiPlugin>())
+        .add_art_piece(OrigamiPlugin)
         .run();
 }
 
+pub struct OrigamiPlugin;
+
+impl ArtPiece for OrigamiPlugin {
+    const NAME: &'static str = "origami";
+    const TITLE: &'static str = "Origami";
+
+    fn camera() -> CameraRig {
+        CameraRig {
+            pan_orbit: PanOrbitCamera {
+                radius: 2.,
+                auto_rotate: Some(AutoRotate::default()),
+                ..Default::default()
+            },
+            settings: PanOrbitSettings {
+                min_radius: 0.1,
+                max_radius: 20.,
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+}
+
+impl Plugin for OrigamiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(SystemSet::on_enter(Self::state()).with_system(setup))
+            .add_system_set(
+                SystemSet::on_exit(Self::state()).with_system(despawn_all::<OrigamiScene>),
+            );
+    }
+}
+
+#[derive(Component)]
+struct OrigamiScene;
+
 fn setup(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
@@ -70,35 +106,13 @@ fn setup(
         },
     };
 
-    commands.spawn_bundle(PbrBundle {
-        mesh: meshes.add(model.mesh()),
-        material: materials.add(Color::rgb(0.8, 0.3, 0.3).into()),
-        ..Default::default()
-    });
-
-    // red point light
-    commands.spawn_bundle(PointLightBundle {
-        // transform: Transform::from_xyz(5.0, 8.0, 2.0),
-        transform: Transform::from_xyz(1.0, 2.0, 0.0),
-        point_light: PointLight {
-            intensity: 1600.0, // lumens - roughly a 100W non-halogen incandescent bulb
-            color: Color::WHITE,
-            shadows_enabled: false,
-            ..Default::default()
-        },
-        ..Default::default()
-    });
-
-    // camera
     commands
-        .spawn_bundle(PerspectiveCameraBundle {
-            transform: Transform::from_xyz(-2.0, 2.5, 5.0).looking_at(Vec3::ZERO, Vec3::Y),
+        .spawn_bundle(PbrBundle {
+            mesh: meshes.add(model.mesh()),
+            material: materials.add(Color::rgb(0.8, 0.3, 0.3).into()),
             ..Default::default()
         })
-        .insert(PanOrbitCamera {
-            radius: 2.,
-            ..Default::default()
-        });
+        .insert(OrigamiScene);
 }
 
 struct OrigamiModel {
@@ -0,0 +1,184 @@
+use bevy::asset::AssetPlugin;
+use bevy::diagnostic::{
+    EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin,
+};
+use bevy::input::system::exit_on_esc_system;
+use bevy::prelude::*;
+
+use crate::art_piece::{ArtPiece, ArtState};
+use crate::asset_folders::AssetFoldersPlugin;
+use crate::camera_bookmarks::CameraBookmarksPlugin;
+use crate::camera_path::CameraPathPlugin;
+use crate::diagnostics_overlay::DiagnosticsOverlayPlugin;
+use crate::fly_camera::FlyCameraPlugin;
+use crate::frame_export::{FrameExport, FrameExportPlugin};
+use crate::frame_selection::FrameSelectionPlugin;
+use crate::launch_config::{LaunchConfig, LaunchConfigPlugin};
+use crate::pan_orbit_camera::constraints::PanOrbitConstraints;
+use crate::pan_orbit_camera::{PanOrbitCamera, PanOrbitCameraPlugin, PanOrbitSettings};
+use crate::params::ParamsPlugin;
+use crate::params_file::ParamsFilePlugin;
+use crate::screenshot::ScreenshotPlugin;
+use crate::seeded_rng::SeededRngPlugin;
+use crate::sim_clock::SimClockPlugin;
+use crate::view_link::ViewLinkPlugin;
+
+/// Everything an art piece needs besides the art: the window, [`DefaultPlugins`], the camera controls,
+/// screenshots, a parameter panel, ESC to exit and a diagnostics overlay.
+/// Add this first, then the pieces with [`crate::art_piece::AddArtPiece::add_art_piece`].
+///
+/// Settings can be passed in with the [`LaunchConfig`], including the `seed` for [`crate::seeded_rng::SeededRng`].
+/// Natively, starting with `--export DIRECTORY` renders an image sequence instead, see [`FrameExport::from_config`].
+pub struct ArtAppPlugin {
+    /// Used to name the files the app saves, like `{name}_bookmarks.ron`
+    pub name: &'static str,
+    pub title: &'static str,
+    /// CSS selector for the canvas to draw into on wasm, `None` adds a new canvas to the page
+    pub canvas: Option<&'static str>,
+    pub msaa_samples: u32,
+    pub initial_state: ArtState,
+    /// Where assets are loaded from, natively every folder is searched in order, see [`AssetFoldersPlugin`]
+    pub asset_folders: &'static [&'static str],
+    /// Log the frame rate and entity count every second
+    pub log_diagnostics: bool,
+}
+
+impl Default for ArtAppPlugin {
+    fn default() -> Self {
+        ArtAppPlugin {
+            name: "art",
+            title: "Art",
+            canvas: Some("#bevy"),
+            msaa_samples: 4,
+            initial_state: ArtState::Menu,
+            asset_folders: &["assets"],
+            log_diagnostics: false,
+        }
+    }
+}
+
+impl ArtAppPlugin {
+    /// An app showing only `P`
+    pub fn piece<P: ArtPiece>() -> Self {
+        ArtAppPlugin {
+            name: P::NAME,
+            title: P::TITLE,
+            initial_state: P::state(),
+            ..Default::default()
+        }
+    }
+}
+
+/// The camera a piece starts with, it orbits, flies and remembers bookmarks and paths
+#[derive(Clone, Debug)]
+pub struct CameraRig {
+    /// Where the camera starts, looking at `pan_orbit.focus`
+    pub translation: Vec3,
+    pub pan_orbit: PanOrbitCamera,
+    pub constraints: PanOrbitConstraints,
+    pub settings: PanOrbitSettings,
+}
+
+impl Default for CameraRig {
+    fn default() -> Self {
+        CameraRig {
+            translation: Vec3::new(-2.0, 2.5, 5.0),
+            pan_orbit: PanOrbitCamera::default(),
+            constraints: PanOrbitConstraints::default(),
+            settings: PanOrbitSettings::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ArtLight {
+    pub translation: Vec3,
+    pub point_light: PointLight,
+}
+
+impl Default for ArtLight {
+    fn default() -> Self {
+        ArtLight {
+            translation: Vec3::new(1.0, 2.0, 0.0),
+            point_light: PointLight {
+                intensity: 1600.0, // lumens - roughly a 100W non-halogen incandescent bulb
+                color: Color::WHITE,
+                shadows_enabled: false,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl Plugin for ArtAppPlugin {
+    fn build(&self, app: &mut App) {
+        let mut config = LaunchConfig::from_environment();
+        let export = FrameExport::from_config(&mut config);
+        // projects read the rest of it as they're added
+        app.insert_resource(config).add_plugin(LaunchConfigPlugin);
+
+        let mut window = WindowDescriptor {
+            title: self.title.to_string(),
+            #[cfg(target_arch = "wasm32")]
+            canvas: self.canvas.map(|canvas| canvas.to_string()),
+            ..Default::default()
+        };
+        if let Some(export) = &export {
+            export.configure_window(&mut window);
+        }
+
+        // these have to be in place before the window and renderer are created
+        app.insert_resource(window)
+            .insert_resource(Msaa {
+                samples: self.msaa_samples,
+            })
+            .add_plugins_with(DefaultPlugins, |group| {
+                group.add_before::<AssetPlugin, _>(AssetFoldersPlugin {
+                    folders: self.asset_folders,
+                })
+            });
+
+        // reload shaders and parameter files when they're saved
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Err(e) = app
+            .world
+            .get_resource::<AssetServer>()
+            .unwrap()
+            .watch_for_changes()
+        {
+            warn!("Assets won't reload when they change: {}", e);
+        }
+
+        app.add_plugin(PanOrbitCameraPlugin)
+            .add_plugin(CameraBookmarksPlugin {
+                file_name: format!("{}_bookmarks.ron", self.name),
+            })
+            .add_plugin(CameraPathPlugin {
+                file_name: format!("{}_camera_path.ron", self.name),
+            })
+            .add_plugin(FrameSelectionPlugin)
+            .add_plugin(ViewLinkPlugin)
+            .add_plugin(FlyCameraPlugin)
+            .add_plugin(ScreenshotPlugin {
+                file_prefix: self.name.to_string(),
+            })
+            .add_system(exit_on_esc_system);
+
+        app.add_plugin(SimClockPlugin)
+            .add_plugin(SeededRngPlugin)
+            .add_plugin(ParamsPlugin)
+            .add_plugin(ParamsFilePlugin);
+        if let Some(export) = export {
+            app.add_plugin(FrameExportPlugin { export });
+        }
+
+        app.add_plugin(FrameTimeDiagnosticsPlugin)
+            .add_plugin(EntityCountDiagnosticsPlugin)
+            .add_plugin(DiagnosticsOverlayPlugin);
+        if self.log_diagnostics {
+            app.add_plugin(LogDiagnosticsPlugin::default());
+        }
+
+        app.add_state(self.initial_state);
+    }
+}
@@ -0,0 +1,201 @@
+//! Art pieces that can run on their own or be switched between at runtime, like in the gallery.
+//! Each piece runs its systems while the app is in its [`ArtState::Piece`], and cleans up after itself when it leaves.
+
+use bevy::prelude::*;
+use bevy::render::camera::Camera;
+
+use crate::art_app::{ArtLight, CameraRig};
+use crate::camera_bookmarks::CameraBookmarks;
+use crate::camera_path::CameraPathRecorder;
+use crate::fly_camera::FlyCamera;
+use crate::pan_orbit_camera::PanOrbitSettings;
+use crate::params::{insert_piece_params, remove_piece_params, ParamsRegistry};
+use crate::view_link::ViewLink;
+
+/// What the app is showing, a piece's own app starts in that piece and never leaves it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ArtState {
+    Menu,
+    /// A piece, by its [`ArtPiece::NAME`]
+    Piece(&'static str),
+}
+
+/// A plugin for one piece of art.
+/// Its systems should only run in its own state: set up in [`SystemSet::on_enter`], update in [`SystemSet::on_update`],
+/// and despawn everything it spawned in [`SystemSet::on_exit`], see [`despawn_all`].
+/// The camera and light are spawned and despawned for it, and it gets its own camera bookmarks and path.
+/// The [`crate::params::Params`] it registers are only resources, in the panel and in the link while it's showing.
+pub trait ArtPiece: Plugin {
+    /// Used for the piece's state and to name the files it saves
+    const NAME: &'static str;
+    const TITLE: &'static str;
+
+    fn camera() -> CameraRig {
+        CameraRig::default()
+    }
+
+    /// `None` for pieces that light themselves
+    fn light() -> Option<ArtLight> {
+        Some(ArtLight::default())
+    }
+
+    fn state() -> ArtState {
+        ArtState::Piece(Self::NAME)
+    }
+}
+
+pub trait AddArtPiece {
+    /// Add `piece`, with its camera rig and files while it's showing and an entry in [`ArtPieces`]
+    fn add_art_piece<P: ArtPiece>(&mut self, piece: P) -> &mut Self;
+}
+
+impl AddArtPiece for App {
+    fn add_art_piece<P: ArtPiece>(&mut self, piece: P) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(ArtPieces::default)
+            .pieces
+            .push(ArtPieceEntry {
+                name: P::NAME,
+                title: P::TITLE,
+            });
+
+        self.add_system_set(
+            SystemSet::on_enter(P::state())
+                // before the piece's own systems, which might read them
+                .with_system(insert_params::<P>.exclusive_system().at_start())
+                .with_system(spawn_rig::<P>)
+                .with_system(load_camera_files::<P>),
+        )
+        .add_system_set(
+            SystemSet::on_exit(P::state())
+                .with_system(despawn_rig)
+                .with_system(remove_params::<P>.exclusive_system().at_end()),
+        );
+
+        // whatever params the piece registers are its own
+        let registered = self
+            .world
+            .get_resource_or_insert_with(ParamsRegistry::default)
+            .len();
+        self.add_plugin(piece);
+        self.world
+            .get_resource_mut::<ParamsRegistry>()
+            .unwrap()
+            .set_piece(registered, P::state());
+        // they're put back when the piece is entered
+        remove_piece_params(&mut self.world, P::state());
+        self
+    }
+}
+
+/// Every piece in the app, in the order they were added
+#[derive(Default)]
+pub struct ArtPieces {
+    pieces: Vec<ArtPieceEntry>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ArtPieceEntry {
+    pub name: &'static str,
+    pub title: &'static str,
+}
+
+impl ArtPieces {
+    pub fn iter(&self) -> impl Iterator<Item = &ArtPieceEntry> {
+        self.pieces.iter()
+    }
+
+    /// The piece after `state`, wrapping around to the first
+    pub fn next(&self, state: &ArtState) -> Option<&ArtPieceEntry> {
+        let next = match state {
+            ArtState::Menu => 0,
+            ArtState::Piece(name) => self
+                .pieces
+                .iter()
+                .position(|piece| piece.name == *name)
+                .map_or(0, |i| i + 1),
+        };
+        self.pieces.get(next % self.pieces.len().max(1))
+    }
+}
+
+/// Despawn every entity with `T` and its children, for cleaning up when leaving a piece
+pub fn despawn_all<T: Component>(mut commands: Commands, query: Query<Entity, With<T>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn insert_params<P: ArtPiece>(world: &mut World) {
+    insert_piece_params(world, P::state());
+}
+
+fn remove_params<P: ArtPiece>(world: &mut World) {
+    remove_piece_params(world, P::state());
+}
+
+/// The camera and light of the piece that's showing
+#[derive(Component)]
+struct PieceRig;
+
+fn spawn_rig<P: ArtPiece>(mut commands: Commands, mut settings: ResMut<PanOrbitSettings>) {
+    let camera = P::camera();
+    if let Some(light) = P::light() {
+        commands
+            .spawn_bundle(PointLightBundle {
+                transform: Transform::from_translation(light.translation),
+                point_light: light.point_light,
+                ..Default::default()
+            })
+            .insert(PieceRig);
+    }
+
+    *settings = camera.settings;
+    commands
+        .spawn_bundle(PerspectiveCameraBundle {
+            transform: Transform::from_translation(camera.translation)
+                .looking_at(camera.pan_orbit.focus, Vec3::Y),
+            ..Default::default()
+        })
+        .insert(camera.pan_orbit)
+        .insert(camera.constraints)
+        .insert(FlyCamera::default())
+        .insert(PieceRig);
+}
+
+/// Switch the bookmarks and camera path over to the piece's own files, the app's are named after the app
+fn load_camera_files<P: ArtPiece>(
+    mut commands: Commands,
+    bookmarks: Res<CameraBookmarks>,
+    recorder: Res<CameraPathRecorder>,
+) {
+    let file_name = format!("{}_bookmarks.ron", P::NAME);
+    if bookmarks.file_name() != file_name {
+        commands.insert_resource(CameraBookmarks::load(&file_name));
+    }
+    let file_name = format!("{}_camera_path.ron", P::NAME);
+    if recorder.file_name() != file_name {
+        commands.insert_resource(CameraPathRecorder::load(&file_name));
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn despawn_rig(
+    mut commands: Commands,
+    mut link: ResMut<ViewLink>,
+    mut windows: ResMut<Windows>,
+    query: Query<(Entity, Option<&Camera>, Option<&FlyCamera>), With<PieceRig>>,
+) {
+    for (entity, camera, fly) in query.iter() {
+        // the next piece, or the menu, needs the cursor back
+        if let (Some(camera), Some(FlyCamera { enabled: true, .. })) = (camera, fly) {
+            if let Some(window) = windows.get_mut(camera.window) {
+                window.set_cursor_lock_mode(false);
+                window.set_cursor_visibility(true);
+            }
+        }
+        commands.entity(entity).despawn_recursive();
+    }
+    // the view belongs to the piece, the next one starts from its own camera
+    link.view = None;
+}
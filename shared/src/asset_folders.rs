@@ -0,0 +1,101 @@
+//! Load assets from more than one folder, so the gallery can use every piece's own `assets` as they are.
+//! Natively the folders are searched in order, on wasm only the first one is used.
+//! Bevy only watches its own single folder for changes, so assets from several folders aren't reloaded when saved.
+
+use bevy::asset::AssetServerSettings;
+use bevy::prelude::*;
+
+/// Add this before [`bevy::asset::AssetPlugin`] so the [`AssetServer`] loads from `folders`
+pub struct AssetFoldersPlugin {
+    /// Relative to the crate when run with cargo, otherwise to the executable
+    pub folders: &'static [&'static str],
+}
+
+impl Plugin for AssetFoldersPlugin {
+    fn build(&self, app: &mut App) {
+        let first = self.folders.first().copied().unwrap_or("assets");
+        app.insert_resource(AssetServerSettings {
+            asset_folder: first.to_string(),
+        });
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if self.folders.len() > 1 {
+            let task_pool = app
+                .world
+                .get_resource::<bevy::tasks::IoTaskPool>()
+                .unwrap()
+                .0
+                .clone();
+            let io = native::AssetFolders {
+                folders: self
+                    .folders
+                    .iter()
+                    .map(bevy::asset::FileAssetIo::new)
+                    .collect(),
+            };
+            app.insert_resource(AssetServer::new(io, task_pool));
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use std::path::{Path, PathBuf};
+
+    use bevy::asset::{AssetIo, AssetIoError, FileAssetIo};
+    use bevy::utils::BoxedFuture;
+
+    pub(super) struct AssetFolders {
+        pub(super) folders: Vec<FileAssetIo>,
+    }
+
+    impl AssetIo for AssetFolders {
+        fn load_path<'a>(
+            &'a self,
+            path: &'a Path,
+        ) -> BoxedFuture<'a, Result<Vec<u8>, AssetIoError>> {
+            Box::pin(async move {
+                for folder in self.folders.iter() {
+                    match folder.load_path(path).await {
+                        Err(AssetIoError::NotFound(_)) => continue,
+                        loaded => return loaded,
+                    }
+                }
+                Err(AssetIoError::NotFound(path.to_path_buf()))
+            })
+        }
+
+        fn read_directory(
+            &self,
+            path: &Path,
+        ) -> Result<Box<dyn Iterator<Item = PathBuf>>, AssetIoError> {
+            let mut paths = Vec::new();
+            for folder in self
+                .folders
+                .iter()
+                .filter(|folder| folder.is_directory(path))
+            {
+                paths.extend(folder.read_directory(path)?);
+            }
+            if paths.is_empty() && !self.is_directory(path) {
+                return Err(AssetIoError::NotFound(path.to_path_buf()));
+            }
+            Ok(Box::new(paths.into_iter()))
+        }
+
+        fn is_directory(&self, path: &Path) -> bool {
+            self.folders.iter().any(|folder| folder.is_directory(path))
+        }
+
+        fn watch_path_for_changes(&self, _path: &Path) -> Result<(), AssetIoError> {
+            Ok(())
+        }
+
+        fn watch_for_changes(&self) -> Result<(), AssetIoError> {
+            Err(AssetIoError::Io(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                "only a single asset folder can be watched",
+            )))
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+
+use crate::pan_orbit_camera::{OrbitView, PanOrbitCamera, PanOrbitSystem, PanOrbitTransition};
+use crate::storage;
+
+/// Save the current [`PanOrbitCamera`] view with ctrl + a number key and fly back to it with the number key.
+/// Bookmarks are kept between sessions in `file_name`, which is also the `localStorage` key on wasm.
+pub struct CameraBookmarksPlugin {
+    pub file_name: String,
+}
+
+impl Plugin for CameraBookmarksPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraBookmarkSettings>()
+            .insert_resource(CameraBookmarks::load(&self.file_name))
+            .add_system(
+                camera_bookmark_input
+                    .after(PanOrbitSystem::Input)
+                    .before(PanOrbitSystem::Camera),
+            );
+    }
+}
+
+pub struct CameraBookmarkSettings {
+    /// Bookmarks are named after the key used to save them
+    pub keys: Vec<(KeyCode, &'static str)>,
+    /// Holding any of these saves the current view instead of recalling it
+    pub save_modifiers: Vec<KeyCode>,
+    /// How long it takes to fly to a bookmark
+    pub transition_seconds: f32,
+}
+
+impl Default for CameraBookmarkSettings {
+    fn default() -> Self {
+        CameraBookmarkSettings {
+            keys: vec![
+                (KeyCode::Key1, "1"),
+                (KeyCode::Key2, "2"),
+                (KeyCode::Key3, "3"),
+                (KeyCode::Key4, "4"),
+                (KeyCode::Key5, "5"),
+                (KeyCode::Key6, "6"),
+                (KeyCode::Key7, "7"),
+                (KeyCode::Key8, "8"),
+                (KeyCode::Key9, "9"),
+                (KeyCode::Key0, "0"),
+            ],
+            save_modifiers: vec![KeyCode::LControl, KeyCode::RControl],
+            transition_seconds: 1.5,
+        }
+    }
+}
+
+/// Named camera views that are saved between sessions
+pub struct CameraBookmarks {
+    file_name: String,
+    views: BTreeMap<String, OrbitView>,
+}
+
+impl CameraBookmarks {
+    /// Load the bookmarks from a previous session, starting with none if they're missing or broken
+    pub fn load(file_name: &str) -> Self {
+        let views = match storage::load(file_name) {
+            Ok(Some(contents)) => ron::from_str(&contents).unwrap_or_else(|e| {
+                warn!("Ignoring invalid camera bookmarks in {}: {}", file_name, e);
+                BTreeMap::new()
+            }),
+            Ok(None) => BTreeMap::new(),
+            Err(e) => {
+                warn!("Failed to load camera bookmarks: {}", e);
+                BTreeMap::new()
+            }
+        };
+
+        CameraBookmarks {
+            file_name: file_name.to_string(),
+            views,
+        }
+    }
+
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let contents = ron::ser::to_string_pretty(&self.views, Default::default())?;
+        storage::save(&self.file_name, &contents)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&OrbitView> {
+        self.views.get(name)
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, view: OrbitView) {
+        self.views.insert(name.into(), view);
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<OrbitView> {
+        self.views.remove(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &OrbitView)> {
+        self.views.iter()
+    }
+}
+
+fn camera_bookmark_input(
+    mut commands: Commands,
+    settings: Res<CameraBookmarkSettings>,
+    input_keyboard: Res<Input<KeyCode>>,
+    mut bookmarks: ResMut<CameraBookmarks>,
+    query: Query<(Entity, &PanOrbitCamera)>,
+) {
+    let saving = settings
+        .save_modifiers
+        .iter()
+        .any(|&key| input_keyboard.pressed(key));
+
+    for &(key, name) in settings.keys.iter() {
+        if !input_keyboard.just_pressed(key) {
+            continue;
+        }
+
+        if saving {
+            if let Some((_, pan_orbit)) = query.iter().next() {
+                bookmarks.insert(name, pan_orbit.view());
+                match bookmarks.save() {
+                    Ok(()) => info!("Saved camera bookmark {}", name),
+                    Err(e) => warn!("Failed to save camera bookmark {}: {}", name, e),
+                }
+            }
+        } else if let Some(&view) = bookmarks.get(name) {
+            for (entity, pan_orbit) in query.iter() {
+                commands.entity(entity).insert(PanOrbitTransition::new(
+                    pan_orbit.view(),
+                    view,
+                    settings.transition_seconds,
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,428 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::pan_orbit_camera::constraints::PanOrbitConstraints;
+use crate::pan_orbit_camera::{OrbitView, PanOrbitCamera, PanOrbitState, PanOrbitSystem};
+use crate::sim_clock::{frame_seconds, SimClock};
+use crate::storage;
+
+/// Record the [`PanOrbitCamera`] as it moves and play it back smoothly for cinematic flythroughs.
+/// R starts and stops recording, K adds a keyframe while recording and P starts and stops playback.
+/// The recorded path is kept between sessions in `file_name`, which is also the `localStorage` key on wasm.
+pub struct CameraPathPlugin {
+    pub file_name: String,
+}
+
+impl Plugin for CameraPathPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraPathSettings>()
+            .insert_resource(CameraPathRecorder::load(&self.file_name))
+            .add_system(camera_path_input.before(PanOrbitSystem::Camera))
+            .add_system(update_camera_path.after(PanOrbitSystem::Camera));
+    }
+}
+
+pub struct CameraPathSettings {
+    pub record_key: KeyCode,
+    pub keyframe_key: KeyCode,
+    pub play_key: KeyCode,
+    /// Automatically add a keyframe this often while recording, `None` only records keyframes added with `keyframe_key`
+    pub record_interval: Option<f32>,
+    /// Advance playback by exactly this many seconds every frame instead of following the camera's
+    /// [`frame_seconds`], so every frame of the path gets rendered when capturing video
+    pub playback_step: Option<f32>,
+}
+
+impl Default for CameraPathSettings {
+    fn default() -> Self {
+        CameraPathSettings {
+            record_key: KeyCode::R,
+            keyframe_key: KeyCode::K,
+            play_key: KeyCode::P,
+            record_interval: Some(0.5),
+            playback_step: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CameraKeyframe {
+    /// Seconds since the start of the path
+    pub time: f32,
+    pub translation: Vec3,
+    pub rotation: Quat,
+    /// What the camera was orbiting around, so the orbit camera can pick up where the path ends
+    pub focus: Vec3,
+}
+
+impl CameraKeyframe {
+    pub fn apply_to(&self, transform: &mut Transform) {
+        transform.translation = self.translation;
+        transform.rotation = self.rotation;
+    }
+
+    pub fn orbit_view(&self) -> OrbitView {
+        let transform = Transform {
+            translation: self.translation,
+            rotation: self.rotation,
+            ..Default::default()
+        };
+        OrbitView::from_transform(&transform, self.translation.distance(self.focus))
+    }
+}
+
+/// Keyframes ordered by time, positions are interpolated with a Catmull-Rom spline and rotations are slerped
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CameraPath {
+    pub keyframes: Vec<CameraKeyframe>,
+}
+
+impl CameraPath {
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |keyframe| keyframe.time)
+    }
+
+    /// Add a keyframe, keeping them in time order
+    pub fn insert(&mut self, keyframe: CameraKeyframe) {
+        let index = self
+            .keyframes
+            .iter()
+            .position(|existing| existing.time > keyframe.time)
+            .unwrap_or(self.keyframes.len());
+        self.keyframes.insert(index, keyframe);
+    }
+
+    /// The interpolated camera at `time`, clamped to the ends of the path
+    pub fn sample(&self, time: f32) -> Option<CameraKeyframe> {
+        let keyframes = &self.keyframes;
+        let first = keyframes.first()?;
+        let last = keyframes.last()?;
+        if time <= first.time {
+            return Some(*first);
+        } else if time >= last.time {
+            return Some(*last);
+        }
+
+        // the keyframes either side of `time`, plus their neighbours to shape the curve
+        let next = keyframes
+            .iter()
+            .position(|keyframe| keyframe.time > time)
+            .unwrap_or(keyframes.len() - 1);
+        let k1 = &keyframes[next - 1];
+        let k2 = &keyframes[next];
+        let k0 = &keyframes[(next - 1).saturating_sub(1)];
+        let k3 = &keyframes[(next + 1).min(keyframes.len() - 1)];
+
+        let t = (time - k1.time) / (k2.time - k1.time);
+
+        // take the short way around
+        let end_rotation = if k1.rotation.dot(k2.rotation) < 0.0 {
+            -k2.rotation
+        } else {
+            k2.rotation
+        };
+
+        Some(CameraKeyframe {
+            time,
+            translation: catmull_rom(
+                k0.translation,
+                k1.translation,
+                k2.translation,
+                k3.translation,
+                t,
+            ),
+            rotation: k1.rotation.slerp(end_rotation, t).normalize(),
+            focus: catmull_rom(k0.focus, k1.focus, k2.focus, k3.focus, t),
+        })
+    }
+}
+
+/// Uniform Catmull-Rom spline between `p1` and `p2`, passing through every control point
+fn catmull_rom(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, t: f32) -> Vec3 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+
+    0.5 * ((2.0 * p1)
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CameraPathState {
+    Idle,
+    Recording { elapsed: f32, since_keyframe: f32 },
+    Playing { elapsed: f32 },
+}
+
+pub struct CameraPathRecorder {
+    file_name: String,
+    pub path: CameraPath,
+    pub state: CameraPathState,
+}
+
+impl CameraPathRecorder {
+    /// Load the path from a previous session, starting empty if it's missing or broken
+    pub fn load(file_name: &str) -> Self {
+        let path = match storage::load(file_name) {
+            Ok(Some(contents)) => ron::from_str(&contents).unwrap_or_else(|e| {
+                warn!("Ignoring invalid camera path in {}: {}", file_name, e);
+                CameraPath::default()
+            }),
+            Ok(None) => CameraPath::default(),
+            Err(e) => {
+                warn!("Failed to load camera path: {}", e);
+                CameraPath::default()
+            }
+        };
+
+        CameraPathRecorder {
+            file_name: file_name.to_string(),
+            path,
+            state: CameraPathState::Idle,
+        }
+    }
+
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let contents = ron::ser::to_string_pretty(&self.path, Default::default())?;
+        storage::save(&self.file_name, &contents)
+    }
+
+    pub fn is_playing(&self) -> bool {
+        matches!(self.state, CameraPathState::Playing { .. })
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn camera_path_input(
+    settings: Res<CameraPathSettings>,
+    input_keyboard: Res<Input<KeyCode>>,
+    mut recorder: ResMut<CameraPathRecorder>,
+    mut query: Query<(
+        &mut PanOrbitCamera,
+        &mut PanOrbitState,
+        &mut Transform,
+        Option<&PanOrbitConstraints>,
+    )>,
+) {
+    // copy the state out so the resource isn't borrowed while we change it
+    let state = recorder.state;
+
+    if input_keyboard.just_pressed(settings.record_key) {
+        match state {
+            CameraPathState::Recording { .. } => {
+                recorder.state = CameraPathState::Idle;
+                match recorder.save() {
+                    Ok(()) => info!(
+                        "Saved a {:.1}s camera path with {} keyframes",
+                        recorder.path.duration(),
+                        recorder.path.keyframes.len()
+                    ),
+                    Err(e) => warn!("Failed to save the camera path: {}", e),
+                }
+            }
+            CameraPathState::Idle => {
+                info!("Recording a camera path");
+                recorder.path = CameraPath::default();
+                recorder.state = CameraPathState::Recording {
+                    elapsed: 0.0,
+                    // start with a keyframe
+                    since_keyframe: f32::INFINITY,
+                };
+            }
+            CameraPathState::Playing { .. } => {}
+        }
+    }
+
+    if input_keyboard.just_pressed(settings.play_key) {
+        match state {
+            CameraPathState::Playing { .. } => {
+                stop_playback(&mut recorder, &mut query);
+            }
+            CameraPathState::Idle if recorder.path.keyframes.len() >= 2 => {
+                recorder.state = CameraPathState::Playing { elapsed: 0.0 };
+                for (mut pan_orbit, _, _, _) in query.iter_mut() {
+                    pan_orbit.enabled = false;
+                }
+            }
+            CameraPathState::Idle => {
+                warn!("Record a camera path with {:?} first", settings.record_key)
+            }
+            CameraPathState::Recording { .. } => {}
+        }
+    }
+
+    if input_keyboard.just_pressed(settings.keyframe_key) {
+        if let CameraPathState::Recording { since_keyframe, .. } = &mut recorder.state {
+            *since_keyframe = f32::INFINITY;
+        }
+    }
+}
+
+/// Record keyframes from the camera or move the camera along the path
+#[allow(clippy::type_complexity)]
+fn update_camera_path(
+    time: Res<Time>,
+    clock: Option<Res<SimClock>>,
+    settings: Res<CameraPathSettings>,
+    mut recorder: ResMut<CameraPathRecorder>,
+    mut query: Query<(
+        &mut PanOrbitCamera,
+        &mut PanOrbitState,
+        &mut Transform,
+        Option<&PanOrbitConstraints>,
+    )>,
+) {
+    let state = recorder.state;
+    match state {
+        CameraPathState::Idle => {}
+        CameraPathState::Recording {
+            elapsed,
+            since_keyframe,
+        } => {
+            let elapsed = elapsed + time.delta_seconds();
+            let mut since_keyframe = since_keyframe + time.delta_seconds();
+
+            // an infinite wait means a keyframe was asked for
+            let due = settings
+                .record_interval
+                .map_or(false, |interval| since_keyframe >= interval);
+            if due || since_keyframe.is_infinite() {
+                if let Some((_, state, transform, _)) = query.iter_mut().next() {
+                    recorder.path.insert(CameraKeyframe {
+                        time: elapsed,
+                        translation: transform.translation,
+                        rotation: transform.rotation,
+                        focus: state.current.focus,
+                    });
+                    since_keyframe = 0.0;
+                }
+            }
+
+            recorder.state = CameraPathState::Recording {
+                elapsed,
+                since_keyframe,
+            };
+        }
+        CameraPathState::Playing { elapsed } => {
+            let elapsed = elapsed
+                + settings
+                    .playback_step
+                    .unwrap_or_else(|| frame_seconds(&time, clock.as_deref()));
+            if let Some(keyframe) = recorder.path.sample(elapsed) {
+                for (_, _, mut transform, _) in query.iter_mut() {
+                    keyframe.apply_to(&mut transform);
+                }
+            }
+
+            if elapsed >= recorder.path.duration() {
+                stop_playback(&mut recorder, &mut query);
+            } else {
+                recorder.state = CameraPathState::Playing { elapsed };
+            }
+        }
+    }
+}
+
+/// Hand the cameras back to the user from wherever the path left them
+#[allow(clippy::type_complexity)]
+fn stop_playback(
+    recorder: &mut CameraPathRecorder,
+    query: &mut Query<(
+        &mut PanOrbitCamera,
+        &mut PanOrbitState,
+        &mut Transform,
+        Option<&PanOrbitConstraints>,
+    )>,
+) {
+    let elapsed = match recorder.state {
+        CameraPathState::Playing { elapsed } => elapsed,
+        _ => return,
+    };
+    recorder.state = CameraPathState::Idle;
+
+    if let Some(keyframe) = recorder.path.sample(elapsed) {
+        let view = keyframe.orbit_view();
+        for (mut pan_orbit, mut state, _, constraints) in query.iter_mut() {
+            state.snap(&mut pan_orbit, view, constraints);
+            pan_orbit.enabled = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyframe(time: f32, translation: Vec3, yaw: f32) -> CameraKeyframe {
+        CameraKeyframe {
+            time,
+            translation,
+            rotation: Quat::from_rotation_y(yaw),
+            focus: Vec3::ZERO,
+        }
+    }
+
+    fn path() -> CameraPath {
+        let mut path = CameraPath::default();
+        path.insert(keyframe(0.0, Vec3::new(0.0, 0.0, 10.0), 0.0));
+        // out of order, insert sorts it
+        path.insert(keyframe(3.0, Vec3::new(-10.0, 2.0, 0.0), 3.0));
+        path.insert(keyframe(1.0, Vec3::new(10.0, 0.0, 0.0), 1.0));
+        path.insert(keyframe(2.0, Vec3::new(0.0, 5.0, -10.0), 2.0));
+        path
+    }
+
+    #[test]
+    fn keyframes_stay_in_time_order() {
+        let times = path()
+            .keyframes
+            .iter()
+            .map(|keyframe| keyframe.time)
+            .collect::<Vec<_>>();
+        assert_eq!(times, vec![0.0, 1.0, 2.0, 3.0]);
+        assert_eq!(path().duration(), 3.0);
+    }
+
+    #[test]
+    fn paths_pass_through_their_keyframes() {
+        let path = path();
+        for keyframe in path.keyframes.iter() {
+            let sampled = path.sample(keyframe.time).unwrap();
+            assert!(sampled.translation.abs_diff_eq(keyframe.translation, 1e-5));
+            assert!(sampled.rotation.abs_diff_eq(keyframe.rotation, 1e-5));
+        }
+    }
+
+    #[test]
+    fn samples_are_clamped_to_the_ends() {
+        let path = path();
+        assert_eq!(path.sample(-1.0), path.keyframes.first().copied());
+        assert_eq!(path.sample(10.0), path.keyframes.last().copied());
+        assert_eq!(CameraPath::default().sample(0.0), None);
+    }
+
+    #[test]
+    fn samples_between_keyframes_are_smooth() {
+        let path = path();
+        // just either side of a keyframe should be close to it
+        let before = path.sample(1.0 - 1e-3).unwrap();
+        let after = path.sample(1.0 + 1e-3).unwrap();
+        assert!(before.translation.distance(after.translation) < 0.1);
+        assert!(before.rotation.angle_between(after.rotation) < 0.01);
+    }
+
+    #[test]
+    fn catmull_rom_hits_the_middle_points() {
+        let (p0, p1, p2, p3) = (Vec3::ZERO, Vec3::X, Vec3::Y, Vec3::Z);
+        assert_eq!(catmull_rom(p0, p1, p2, p3, 0.0), p1);
+        assert!(catmull_rom(p0, p1, p2, p3, 1.0).abs_diff_eq(p2, 1e-6));
+        // evenly spaced points on a line stay on it
+        let halfway = catmull_rom(Vec3::ZERO, Vec3::X, 2.0 * Vec3::X, 3.0 * Vec3::X, 0.5);
+        assert!(halfway.abs_diff_eq(1.5 * Vec3::X, 1e-6));
+    }
+}
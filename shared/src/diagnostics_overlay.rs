@@ -0,0 +1,235 @@
+//! An on-screen overlay with the frame rate, a frame time graph, the entity count
+//! and any counters projects register with [`RegisterDiagnostic::register_diagnostic`].
+//! [`DiagnosticsOverlaySettings::toggle_key`] shows and hides it.
+
+use std::collections::VecDeque;
+
+use bevy::diagnostic::{
+    Diagnostic, DiagnosticId, Diagnostics, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin,
+};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+/// Counters that haven't been measured for this long are hidden, like those of a piece that isn't showing
+const STALE_SECONDS: f32 = 1.0;
+
+/// Draws the overlay from [`FrameTimeDiagnosticsPlugin`], [`EntityCountDiagnosticsPlugin`] and the registered counters.
+/// Add it after [`crate::params::ParamsPlugin`], which sets up egui.
+pub struct DiagnosticsOverlayPlugin;
+
+impl Plugin for DiagnosticsOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DiagnosticsOverlaySettings>()
+            .init_resource::<OverlayDiagnostics>()
+            .init_resource::<FrameTimeGraph>()
+            .add_system(toggle_diagnostics_overlay)
+            .add_system(record_frame_time.label(RecordFrameTime))
+            .add_system(diagnostics_overlay.after(RecordFrameTime));
+    }
+}
+
+#[derive(SystemLabel, Clone, Hash, Debug, PartialEq, Eq)]
+struct RecordFrameTime;
+
+pub struct DiagnosticsOverlaySettings {
+    pub toggle_key: KeyCode,
+    pub visible: bool,
+    /// How many frames the frame time graph shows
+    pub graph_frames: usize,
+}
+
+impl Default for DiagnosticsOverlaySettings {
+    fn default() -> Self {
+        DiagnosticsOverlaySettings {
+            toggle_key: KeyCode::F3,
+            visible: false,
+            graph_frames: 240,
+        }
+    }
+}
+
+pub trait RegisterDiagnostic {
+    /// Add `diagnostic` to [`Diagnostics`] and list it in the overlay under the built in ones.
+    /// Measure it with [`Diagnostics::add_measurement`], the overlay shows the average of its history.
+    fn register_diagnostic(&mut self, diagnostic: Diagnostic) -> &mut Self;
+}
+
+impl RegisterDiagnostic for App {
+    fn register_diagnostic(&mut self, diagnostic: Diagnostic) -> &mut Self {
+        self.world
+            .get_resource_or_insert_with(OverlayDiagnostics::default)
+            .ids
+            .push(diagnostic.id);
+        self.world
+            .get_resource_or_insert_with(Diagnostics::default)
+            .add(diagnostic);
+        self
+    }
+}
+
+#[derive(Default)]
+struct OverlayDiagnostics {
+    ids: Vec<DiagnosticId>,
+}
+
+/// The diagnostic only keeps a short history for its average, the graph wants a longer one
+#[derive(Default)]
+struct FrameTimeGraph {
+    seconds: VecDeque<f64>,
+}
+
+fn toggle_diagnostics_overlay(
+    input_keyboard: Res<Input<KeyCode>>,
+    mut settings: ResMut<DiagnosticsOverlaySettings>,
+) {
+    if input_keyboard.just_pressed(settings.toggle_key) {
+        settings.visible = !settings.visible;
+    }
+}
+
+/// Always recorded so the graph is already full when the overlay is shown
+fn record_frame_time(
+    settings: Res<DiagnosticsOverlaySettings>,
+    diagnostics: Res<Diagnostics>,
+    mut graph: ResMut<FrameTimeGraph>,
+) {
+    let frame_time = match diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        // `value` is the oldest measurement, the newest is first
+        .and_then(|diagnostic| diagnostic.measurements().next())
+    {
+        Some(measurement) => measurement.value,
+        None => return,
+    };
+
+    graph.seconds.push_back(frame_time);
+    while graph.seconds.len() > settings.graph_frames {
+        graph.seconds.pop_front();
+    }
+}
+
+fn diagnostics_overlay(
+    mut egui_context: ResMut<EguiContext>,
+    settings: Res<DiagnosticsOverlaySettings>,
+    diagnostics: Res<Diagnostics>,
+    overlay: Res<OverlayDiagnostics>,
+    graph: Res<FrameTimeGraph>,
+) {
+    if !settings.visible {
+        return;
+    }
+
+    let average = |id| {
+        diagnostics
+            .get(id)
+            .and_then(|diagnostic| diagnostic.average())
+    };
+    egui::Window::new("Diagnostics")
+        .anchor(egui::Align2::RIGHT_TOP, [-8.0, 8.0])
+        .resizable(false)
+        .collapsible(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            egui::Grid::new("diagnostics")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("fps");
+                    ui.label(format_average(
+                        average(FrameTimeDiagnosticsPlugin::FPS),
+                        1.0,
+                        "",
+                    ));
+                    ui.end_row();
+                    ui.label("frame time");
+                    ui.label(format_average(
+                        average(FrameTimeDiagnosticsPlugin::FRAME_TIME),
+                        1000.0,
+                        "ms",
+                    ));
+                    ui.end_row();
+                    ui.label("entities");
+                    ui.label(format_average(
+                        average(EntityCountDiagnosticsPlugin::ENTITY_COUNT),
+                        1.0,
+                        "",
+                    ));
+                    ui.end_row();
+
+                    let measured = overlay
+                        .ids
+                        .iter()
+                        .filter_map(|id| diagnostics.get(*id))
+                        .filter(|diagnostic| {
+                            // the newest measurement is first
+                            diagnostic
+                                .measurements()
+                                .next()
+                                .map_or(false, |measurement| {
+                                    measurement.time.elapsed().as_secs_f32() < STALE_SECONDS
+                                })
+                        });
+                    for diagnostic in measured {
+                        ui.label(diagnostic.name.replace('_', " "));
+                        ui.label(format_average(
+                            diagnostic.average(),
+                            1.0,
+                            &diagnostic.suffix,
+                        ));
+                        ui.end_row();
+                    }
+                });
+
+            frame_time_graph(ui, &graph.seconds, settings.graph_frames);
+        });
+}
+
+/// One bar per frame, with lines at 60 and 30 fps
+fn frame_time_graph(ui: &mut egui::Ui, seconds: &VecDeque<f64>, frames: usize) {
+    // anything slower than 15fps is off the top
+    const MAX_SECONDS: f64 = 1.0 / 15.0;
+
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(240.0, 60.0), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, egui::Color32::from_black_alpha(128));
+
+    let height = |seconds: f64| {
+        rect.bottom() - (seconds.min(MAX_SECONDS) / MAX_SECONDS) as f32 * rect.height()
+    };
+    let bar_width = rect.width() / frames.max(1) as f32;
+    // newest on the right
+    let first = frames.saturating_sub(seconds.len());
+    for (i, frame_time) in seconds.iter().enumerate() {
+        let x = rect.left() + (first + i) as f32 * bar_width;
+        let color = if *frame_time > 1.0 / 30.0 {
+            egui::Color32::RED
+        } else if *frame_time > 1.0 / 60.0 {
+            egui::Color32::YELLOW
+        } else {
+            egui::Color32::GREEN
+        };
+        painter.rect_filled(
+            egui::Rect::from_min_max(
+                egui::pos2(x, height(*frame_time)),
+                egui::pos2(x + bar_width, rect.bottom()),
+            ),
+            0.0,
+            color,
+        );
+    }
+
+    for fps in [60.0, 30.0] {
+        let y = height(1.0 / fps);
+        painter.line_segment(
+            [egui::pos2(rect.left(), y), egui::pos2(rect.right(), y)],
+            egui::Stroke::new(1.0, egui::Color32::from_white_alpha(64)),
+        );
+    }
+}
+
+fn format_average(value: Option<f64>, scale: f64, suffix: &str) -> String {
+    match value {
+        // counters are whole numbers, only bother with decimals for small values
+        Some(value) if value * scale >= 100.0 => format!("{:.0}{}", value * scale, suffix),
+        Some(value) => format!("{:.2}{}", value * scale, suffix),
+        None => "-".to_string(),
+    }
+}
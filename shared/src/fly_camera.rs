@@ -0,0 +1,206 @@
+use bevy::input::mouse::{MouseMotion, MouseWheel};
+use bevy::input::InputSystem;
+use bevy::prelude::*;
+use bevy::render::camera::Camera;
+
+use crate::pan_orbit_camera::constraints::PanOrbitConstraints;
+use crate::pan_orbit_camera::{
+    focused_window, OrbitView, PanOrbitCamera, PanOrbitSettings, PanOrbitState, PanOrbitSystem,
+};
+use crate::sim_clock::{frame_seconds, SimClock};
+
+/// Adds the [`FlyCameraSettings`] resource and the systems to fly a [`FlyCamera`] around.
+/// Cameras with both a `FlyCamera` and a [`PanOrbitCamera`] switch between them with [`FlyCameraSettings::toggle_key`].
+/// While one is flying its movement keys are only held down, never just pressed, so they don't trigger anything else.
+pub struct FlyCameraPlugin;
+
+impl Plugin for FlyCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FlyCameraSettings>()
+            .add_system_to_stage(CoreStage::PreUpdate, consume_fly_keys.after(InputSystem))
+            .add_system(toggle_fly_camera.before(PanOrbitSystem::Camera))
+            .add_system(fly_camera.after(PanOrbitSystem::Camera));
+    }
+}
+
+/// First person controls, look around with the mouse and move with the keyboard
+#[derive(Component)]
+pub struct FlyCamera {
+    pub enabled: bool,
+    /// Units per second
+    pub speed: f32,
+    /// Rotation around the global y axis in radians
+    pub yaw: f32,
+    /// Rotation around the local x axis in radians
+    pub pitch: f32,
+}
+
+impl Default for FlyCamera {
+    fn default() -> Self {
+        FlyCamera {
+            enabled: false,
+            speed: 1.0,
+            yaw: 0.0,
+            pitch: 0.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FlyCameraSettings {
+    /// Switch between flying and orbiting
+    pub toggle_key: KeyCode,
+    pub forward: KeyCode,
+    pub back: KeyCode,
+    pub left: KeyCode,
+    pub right: KeyCode,
+    pub up: KeyCode,
+    pub down: KeyCode,
+    /// Radians per pixel of mouse movement
+    pub look_sensitivity: f32,
+    /// Each unit of scroll multiplies the speed by this much
+    pub scroll_speed_factor: f32,
+    pub min_speed: f32,
+    pub max_speed: f32,
+}
+
+impl Default for FlyCameraSettings {
+    fn default() -> Self {
+        FlyCameraSettings {
+            toggle_key: KeyCode::Tab,
+            forward: KeyCode::W,
+            back: KeyCode::S,
+            left: KeyCode::A,
+            right: KeyCode::D,
+            up: KeyCode::Space,
+            down: KeyCode::LControl,
+            look_sensitivity: 0.003,
+            scroll_speed_factor: 1.1,
+            min_speed: 0.001,
+            max_speed: 1000.0,
+        }
+    }
+}
+
+/// Keep space from pausing the clock while it's rising and so on
+fn consume_fly_keys(
+    settings: Res<FlyCameraSettings>,
+    mut input_keyboard: ResMut<Input<KeyCode>>,
+    query: Query<&FlyCamera>,
+) {
+    if query.iter().any(|fly| fly.enabled) {
+        for key in [
+            settings.forward,
+            settings.back,
+            settings.left,
+            settings.right,
+            settings.up,
+            settings.down,
+        ] {
+            input_keyboard.clear_just_pressed(key);
+        }
+    }
+}
+
+/// Swap between flying and orbiting without moving the camera
+#[allow(clippy::type_complexity)]
+fn toggle_fly_camera(
+    settings: Res<FlyCameraSettings>,
+    pan_orbit_settings: Res<PanOrbitSettings>,
+    input_keyboard: Res<Input<KeyCode>>,
+    mut windows: ResMut<Windows>,
+    mut query: Query<(
+        &mut FlyCamera,
+        &mut PanOrbitCamera,
+        &mut PanOrbitState,
+        &Transform,
+        &Camera,
+        Option<&PanOrbitConstraints>,
+    )>,
+) {
+    if !input_keyboard.just_pressed(settings.toggle_key) {
+        return;
+    }
+
+    for (mut fly, mut pan_orbit, mut state, transform, camera, constraints) in query.iter_mut() {
+        if fly.enabled {
+            // orbit around whatever is in front of us, as far along the view as the point we were orbiting before
+            let radius = (state.current.focus - transform.translation).dot(transform.forward());
+            let radius = pan_orbit_settings.clamp_radius(radius, constraints);
+            let view = OrbitView::from_transform(transform, radius);
+            state.snap(&mut pan_orbit, view, constraints);
+            pan_orbit.enabled = true;
+            fly.enabled = false;
+        } else {
+            let view = OrbitView::from_transform(transform, state.current.radius);
+            fly.yaw = view.yaw;
+            fly.pitch = view.pitch;
+            // cross the orbit in a couple of seconds, regardless of the scale of the scene
+            fly.speed = (state.current.radius / 2.0).clamp(settings.min_speed, settings.max_speed);
+            fly.enabled = true;
+            pan_orbit.enabled = false;
+        }
+
+        if let Some(window) = windows.get_mut(camera.window) {
+            window.set_cursor_lock_mode(fly.enabled);
+            window.set_cursor_visibility(!fly.enabled);
+        }
+    }
+}
+
+/// Look with the mouse, move with WASD, rise and fall with space and ctrl, and scroll to change speed
+#[allow(clippy::too_many_arguments)]
+pub fn fly_camera(
+    time: Res<Time>,
+    clock: Option<Res<SimClock>>,
+    windows: Res<Windows>,
+    settings: Res<FlyCameraSettings>,
+    input_keyboard: Res<Input<KeyCode>>,
+    mut ev_motion: EventReader<MouseMotion>,
+    mut ev_scroll: EventReader<MouseWheel>,
+    mut query: Query<(&mut FlyCamera, &mut Transform, &Camera)>,
+) {
+    let focused = focused_window(&windows).map(|(window_id, _)| window_id);
+    let motion = ev_motion
+        .iter()
+        .fold(Vec2::ZERO, |motion, ev| motion + ev.delta);
+    let scroll = ev_scroll.iter().fold(0.0, |scroll, ev| scroll + ev.y);
+
+    for (mut fly, mut transform, camera) in query.iter_mut() {
+        if !fly.enabled || focused != Some(camera.window) {
+            continue;
+        }
+
+        fly.yaw -= motion.x * settings.look_sensitivity;
+        // stop just short of straight up or down so we never flip over
+        let max_pitch = std::f32::consts::FRAC_PI_2 - 0.01;
+        fly.pitch = (fly.pitch - motion.y * settings.look_sensitivity).clamp(-max_pitch, max_pitch);
+        if scroll != 0.0 {
+            fly.speed = (fly.speed * settings.scroll_speed_factor.powf(scroll))
+                .clamp(settings.min_speed, settings.max_speed);
+        }
+
+        transform.rotation = Quat::from_rotation_y(fly.yaw) * Quat::from_rotation_x(fly.pitch);
+
+        let forward = transform.rotation * -Vec3::Z;
+        let right = transform.rotation * Vec3::X;
+        let mut direction = Vec3::ZERO;
+        for (key, key_direction) in [
+            (settings.forward, forward),
+            (settings.back, -forward),
+            (settings.right, right),
+            (settings.left, -right),
+            (settings.up, Vec3::Y),
+            (settings.down, -Vec3::Y),
+        ] {
+            if input_keyboard.pressed(key) {
+                direction += key_direction;
+            }
+        }
+
+        if direction != Vec3::ZERO {
+            transform.translation +=
+                direction.normalize() * fly.speed * frame_seconds(&time, clock.as_deref());
+        }
+    }
+}
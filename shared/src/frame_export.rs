@@ -0,0 +1,221 @@
+//! Render a numbered image sequence on a fixed clock for making videos.
+//! Every frame advances the [`SimClock`] by exactly one video frame however long it took to render and save,
+//! so the same export always comes out the same and nothing gets dropped.
+//! The camera moves by the same fixed step, see [`crate::sim_clock::frame_seconds`].
+//!
+//! Frames are rendered offscreen at the export's size, whatever size the window is.
+
+use std::path::PathBuf;
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::render::camera::{Camera, CameraProjection};
+use bevy::window::WindowId;
+
+use crate::launch_config::LaunchConfig;
+use crate::screenshot::{ScreenshotRequest, Screenshots};
+use crate::sim_clock::{SimClock, SimClockSystem};
+
+/// Don't let rendering get further than this many frames ahead of saving them
+const MAX_FRAMES_IN_FLIGHT: usize = 8;
+
+/// Export `export` from the primary window's 3d camera then exit
+pub struct FrameExportPlugin {
+    pub export: FrameExport,
+}
+
+impl Plugin for FrameExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(self.export.clone())
+            .add_startup_system(start_frame_export)
+            .add_system_to_stage(CoreStage::First, export_frames.before(SimClockSystem))
+            // after bevy has fitted the projections to the window
+            .add_system_to_stage(CoreStage::Last, fit_projection::<PerspectiveProjection>)
+            .add_system_to_stage(CoreStage::Last, fit_projection::<OrthographicProjection>);
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FrameExport {
+    /// Frames are written here as `frame_00000.png`, `frame_00001.png` and so on
+    pub directory: PathBuf,
+    pub fps: f32,
+    pub frames: u32,
+    pub width: u32,
+    pub height: u32,
+    pub supersampling: u32,
+    /// Frames to render with the clock stopped before exporting, so shaders have time to compile
+    pub warmup_frames: u32,
+}
+
+impl Default for FrameExport {
+    fn default() -> Self {
+        FrameExport {
+            directory: PathBuf::from("frames"),
+            fps: 60.0,
+            frames: 600,
+            width: 1920,
+            height: 1080,
+            supersampling: 1,
+            warmup_frames: 30,
+        }
+    }
+}
+
+impl FrameExport {
+    /// The export asked for on the command line, if any.
+    ///
+    /// `--export DIRECTORY` turns it on, then `--export-fps 60`, `--export-frames 600` or `--export-seconds 10`,
+    /// `--export-size 1920x1080` and `--export-supersampling 2` change the defaults.
+    /// This runs before logging is set up, so invalid values are reported through [`LaunchConfig::warn`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_config(config: &mut LaunchConfig) -> Option<Self> {
+        let mut export = FrameExport {
+            directory: PathBuf::from(config.get("export")?),
+            ..Default::default()
+        };
+        let mut positive = |key: &str| {
+            let parsed = config.parse::<f32>(key);
+            if parsed.map_or(false, |value| value <= 0.0) {
+                config.warn(format!("Ignoring --{}, it has to be more than 0", key));
+                return None;
+            }
+            parsed
+        };
+
+        if let Some(fps) = positive("export-fps") {
+            export.fps = fps;
+        }
+        if let Some(frames) = positive("export-frames") {
+            export.frames = frames.round() as u32;
+        } else if let Some(seconds) = positive("export-seconds") {
+            export.frames = (seconds * export.fps).ceil() as u32;
+        }
+        if let Some(supersampling) = positive("export-supersampling") {
+            export.supersampling = supersampling.round().max(1.0) as u32;
+        }
+        if let Some(size) = config.get("export-size").map(str::to_string) {
+            match size
+                .split_once('x')
+                .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+            {
+                Some((width, height)) if width > 0 && height > 0 => {
+                    export.width = width;
+                    export.height = height;
+                }
+                _ => config.warn(format!("Ignoring invalid --export-size: {}", size)),
+            }
+        }
+
+        Some(export)
+    }
+
+    /// There's nowhere to write the frames in the browser
+    #[cfg(target_arch = "wasm32")]
+    pub fn from_config(_config: &mut LaunchConfig) -> Option<Self> {
+        None
+    }
+
+    /// Change the window so frames are rendered as fast as they can be saved, not in time with the screen
+    pub fn configure_window(&self, window: &mut WindowDescriptor) {
+        window.vsync = false;
+    }
+}
+
+fn start_frame_export(
+    export: Res<FrameExport>,
+    mut clock: ResMut<SimClock>,
+    mut exit: EventWriter<AppExit>,
+) {
+    if let Err(e) = std::fs::create_dir_all(&export.directory) {
+        error!(
+            "Failed to create {} to export to: {}",
+            export.directory.display(),
+            e
+        );
+        exit.send(AppExit);
+        return;
+    }
+    info!(
+        "Exporting {} frames at {} fps to {}",
+        export.frames,
+        export.fps,
+        export.directory.display()
+    );
+
+    // hold everything still until the export starts
+    clock.paused = true;
+    // the camera and camera paths follow the clock's fixed step, so they hold still with it until then
+    clock.fixed_step = Some(1.0 / export.fps);
+}
+
+/// Runs before the clock is advanced, so it can hold the clock still on frames that aren't exported
+fn export_frames(
+    export: Res<FrameExport>,
+    mut frame: Local<u32>,
+    mut clock: ResMut<SimClock>,
+    mut screenshots: ResMut<Screenshots>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let index = match frame.checked_sub(export.warmup_frames) {
+        Some(index) => index,
+        None => {
+            *frame += 1;
+            return;
+        }
+    };
+
+    if index >= export.frames {
+        if screenshots.in_flight() == 0 {
+            info!(
+                "Finished exporting {} frames to {}",
+                export.frames,
+                export.directory.display()
+            );
+            exit.send(AppExit);
+        }
+        return;
+    }
+
+    // the renderer is much faster than the PNG encoder, so hold everything still until it catches up
+    if screenshots.in_flight() >= MAX_FRAMES_IN_FLIGHT {
+        clock.paused = true;
+        return;
+    }
+
+    if index == 0 {
+        // the first exported frame is at 0 seconds, every one after it one frame later
+        clock.reset();
+        clock.speed = 1.0;
+    } else {
+        clock.paused = false;
+    }
+
+    screenshots.request(ScreenshotRequest {
+        window: WindowId::primary(),
+        file_name: export
+            .directory
+            .join(format!("frame_{:05}.png", index))
+            .to_string_lossy()
+            .into_owned(),
+        supersampling: export.supersampling,
+        size: Some((export.width, export.height)),
+    });
+    *frame += 1;
+    if (index + 1) % export.fps.round().max(1.0) as u32 == 0 {
+        info!("Rendered {}/{} frames", index + 1, export.frames);
+    }
+}
+
+/// Match the cameras' aspect ratio to the export instead of the window.
+/// This only sets the projection matrix, so bevy still fits the projection itself to the window.
+fn fit_projection<T: CameraProjection + Component + Clone>(
+    export: Res<FrameExport>,
+    mut query: Query<(&mut Camera, &T)>,
+) {
+    for (mut camera, projection) in query.iter_mut() {
+        let mut projection = projection.clone();
+        projection.update(export.width as f32, export.height as f32);
+        camera.projection_matrix = projection.get_projection_matrix();
+    }
+}
@@ -0,0 +1,362 @@
+use bevy::prelude::*;
+use bevy::render::camera::{Camera, OrthographicProjection, PerspectiveProjection};
+use bevy::render::primitives::Aabb;
+
+use crate::pan_orbit_camera::{PanOrbitCamera, PanOrbitSystem, PanOrbitTransition};
+
+/// Move the [`PanOrbitCamera`] so something fills the view.
+/// F frames the [`Selected`] entities and Home frames every visible mesh, or send a [`FrameEvent`].
+/// Alt + click selects the mesh under the cursor, projects can also mark their own [`Selected`] entities.
+pub struct FrameSelectionPlugin;
+
+impl Plugin for FrameSelectionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FrameSelectionSettings>()
+            .add_event::<FrameEvent>()
+            .add_system(frame_selection_input.before(FrameSelectionSystem))
+            .add_system(click_to_select.before(FrameSelectionSystem))
+            .add_system(
+                frame_selection
+                    .label(FrameSelectionSystem)
+                    .after(PanOrbitSystem::Input)
+                    .before(PanOrbitSystem::Camera),
+            );
+    }
+}
+
+/// Moves the cameras for the [`FrameEvent`]s, anything that sends them should run before this
+#[derive(SystemLabel, Clone, Hash, Debug, PartialEq, Eq)]
+pub struct FrameSelectionSystem;
+
+pub struct FrameSelectionSettings {
+    pub selection_key: KeyCode,
+    pub all_key: KeyCode,
+    /// Clicking with `select_modifier` held selects the mesh under the cursor, or clears the selection if there isn't one
+    pub select_button: MouseButton,
+    pub select_modifier: KeyCode,
+    /// How long it takes to fly to the new view
+    pub transition_seconds: f32,
+    /// Leave this much room around the bounds, 1 fits them exactly
+    pub padding: f32,
+}
+
+impl Default for FrameSelectionSettings {
+    fn default() -> Self {
+        FrameSelectionSettings {
+            selection_key: KeyCode::F,
+            all_key: KeyCode::Home,
+            select_button: MouseButton::Left,
+            select_modifier: KeyCode::LAlt,
+            transition_seconds: 0.75,
+            padding: 1.1,
+        }
+    }
+}
+
+/// Marks the entities that F frames
+#[derive(Component, Default)]
+pub struct Selected;
+
+#[derive(Clone, Debug)]
+pub enum FrameEvent {
+    /// An entity's mesh bounds, or just its position if it doesn't have any
+    Entity(Entity),
+    /// World space bounds
+    Bounds(Aabb),
+    /// Every [`Selected`] entity, or everything if nothing is selected
+    Selection,
+    /// Every visible mesh
+    All,
+}
+
+fn frame_selection_input(
+    settings: Res<FrameSelectionSettings>,
+    input_keyboard: Res<Input<KeyCode>>,
+    mut events: EventWriter<FrameEvent>,
+) {
+    if input_keyboard.just_pressed(settings.selection_key) {
+        events.send(FrameEvent::Selection);
+    }
+    if input_keyboard.just_pressed(settings.all_key) {
+        events.send(FrameEvent::All);
+    }
+}
+
+/// Replace the selection with the nearest visible mesh under the cursor
+#[allow(clippy::too_many_arguments)]
+fn click_to_select(
+    mut commands: Commands,
+    settings: Res<FrameSelectionSettings>,
+    windows: Res<Windows>,
+    input_mouse: Res<Input<MouseButton>>,
+    input_keyboard: Res<Input<KeyCode>>,
+    meshes: Query<(Entity, &Aabb, &GlobalTransform, &Visibility)>,
+    selected: Query<Entity, With<Selected>>,
+    cameras: Query<(&Camera, &GlobalTransform, &PanOrbitCamera)>,
+) {
+    if !input_mouse.just_pressed(settings.select_button)
+        || !input_keyboard.pressed(settings.select_modifier)
+    {
+        return;
+    }
+
+    // the camera in the window that was clicked
+    let ray = cameras
+        .iter()
+        .filter(|(_, _, pan_orbit)| pan_orbit.enabled)
+        .find_map(|(camera, transform, _)| {
+            let window = windows.get(camera.window)?;
+            let size = Vec2::new(window.width(), window.height());
+            cursor_ray(camera, transform, window.cursor_position()?, size)
+        });
+    let (origin, direction) = match ray {
+        Some(ray) => ray,
+        None => return,
+    };
+
+    let hit = meshes
+        .iter()
+        .filter(|(_, _, _, visibility)| visibility.is_visible)
+        .filter_map(|(entity, aabb, transform, _)| {
+            ray_distance(origin, direction, &world_bounds(aabb, transform))
+                .map(|distance| (entity, distance))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b));
+
+    for entity in selected.iter() {
+        commands.entity(entity).remove::<Selected>();
+    }
+    if let Some((entity, _)) = hit {
+        commands.entity(entity).insert(Selected);
+    }
+}
+
+fn frame_selection(
+    mut commands: Commands,
+    settings: Res<FrameSelectionSettings>,
+    mut events: EventReader<FrameEvent>,
+    meshes: Query<(&Aabb, &GlobalTransform, &Visibility, Option<&Selected>)>,
+    transforms: Query<&GlobalTransform>,
+    cameras: Query<(
+        Entity,
+        &PanOrbitCamera,
+        Option<&PerspectiveProjection>,
+        Option<&OrthographicProjection>,
+    )>,
+) {
+    for event in events.iter() {
+        let visible = || {
+            meshes
+                .iter()
+                .filter(|(_, _, visibility, _)| visibility.is_visible)
+        };
+        let bounds = match event {
+            FrameEvent::Entity(entity) => match meshes.get(*entity) {
+                Ok((aabb, transform, _, _)) => Some(world_bounds(aabb, transform)),
+                Err(_) => transforms.get(*entity).ok().map(|transform| Aabb {
+                    center: transform.translation,
+                    half_extents: Vec3::ZERO,
+                }),
+            },
+            FrameEvent::Bounds(aabb) => Some(aabb.clone()),
+            FrameEvent::Selection => {
+                let selected = merge_bounds(
+                    visible()
+                        .filter(|(_, _, _, selected)| selected.is_some())
+                        .map(|(aabb, transform, _, _)| world_bounds(aabb, transform)),
+                );
+                selected.or_else(|| {
+                    merge_bounds(
+                        visible().map(|(aabb, transform, _, _)| world_bounds(aabb, transform)),
+                    )
+                })
+            }
+            FrameEvent::All => {
+                merge_bounds(visible().map(|(aabb, transform, _, _)| world_bounds(aabb, transform)))
+            }
+        };
+
+        let bounds = match bounds {
+            Some(bounds) => bounds,
+            None => {
+                warn!("Nothing to frame for {:?}", event);
+                continue;
+            }
+        };
+        let sphere_radius = bounds.half_extents.length() * settings.padding;
+
+        for (entity, pan_orbit, perspective, orthographic) in cameras.iter() {
+            if !pan_orbit.enabled {
+                continue;
+            }
+
+            let mut view = pan_orbit.view();
+            view.focus = bounds.center;
+            let mut scale = None;
+            if let Some(perspective) = perspective {
+                // a point has no size, so keep the current distance
+                if sphere_radius > 0.0 {
+                    view.radius = fit_radius(perspective, sphere_radius);
+                }
+            } else if let Some(orthographic) = orthographic {
+                // moving closer doesn't change what an orthographic camera sees, so scale the projection instead
+                let size = Vec2::new(
+                    orthographic.right - orthographic.left,
+                    orthographic.top - orthographic.bottom,
+                );
+                if sphere_radius > 0.0 && size.min_element() > 0.0 {
+                    scale = Some((orthographic.scale, 2.0 * sphere_radius / size.min_element()));
+                }
+            }
+
+            let mut transition =
+                PanOrbitTransition::new(pan_orbit.view(), view, settings.transition_seconds);
+            if let Some((from, to)) = scale {
+                transition = transition.with_scale(from, to);
+            }
+            commands.entity(entity).insert(transition);
+        }
+    }
+}
+
+/// How far away a perspective camera has to be for a sphere to fit in its narrowest field of view
+pub fn fit_radius(projection: &PerspectiveProjection, sphere_radius: f32) -> f32 {
+    let half_vertical = projection.fov / 2.0;
+    let half_horizontal = (half_vertical.tan() * projection.aspect_ratio).atan();
+    sphere_radius / half_vertical.min(half_horizontal).sin()
+}
+
+/// The world space ray from a camera through `cursor`, in pixels from the bottom left of a window `window_size` big.
+/// Returns where it starts on the near plane and its direction
+pub fn cursor_ray(
+    camera: &Camera,
+    transform: &GlobalTransform,
+    cursor: Vec2,
+    window_size: Vec2,
+) -> Option<(Vec3, Vec3)> {
+    let ndc = cursor / window_size * 2.0 - Vec2::ONE;
+    let ndc_to_world = transform.compute_matrix() * camera.projection_matrix.inverse();
+    // depth is reversed, the near plane is at 1 and a perspective camera's 0 is infinitely far away
+    let near = ndc_to_world.project_point3(ndc.extend(1.0));
+    let far = ndc_to_world.project_point3(ndc.extend(0.5));
+    let direction = (far - near).normalize_or_zero();
+    if direction == Vec3::ZERO || !near.is_finite() {
+        return None;
+    }
+    Some((near, direction))
+}
+
+/// How far along a ray it first hits `aabb`, 0 if it starts inside and `None` if it misses
+pub fn ray_distance(origin: Vec3, direction: Vec3, aabb: &Aabb) -> Option<f32> {
+    let min = aabb.center - aabb.half_extents;
+    let max = aabb.center + aabb.half_extents;
+    let mut enter = 0.0f32;
+    let mut exit = f32::INFINITY;
+    for axis in 0..3 {
+        if direction[axis] == 0.0 {
+            // parallel to this pair of planes, so it's either always between them or never
+            if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                return None;
+            }
+            continue;
+        }
+
+        // the distances along the ray to the pair of planes bounding the box on this axis
+        let to_min = (min[axis] - origin[axis]) / direction[axis];
+        let to_max = (max[axis] - origin[axis]) / direction[axis];
+        enter = enter.max(to_min.min(to_max));
+        exit = exit.min(to_min.max(to_max));
+    }
+
+    if enter <= exit && enter.is_finite() {
+        Some(enter)
+    } else {
+        None
+    }
+}
+
+/// Axis aligned bounds around a mesh's bounds after it's been moved, rotated and scaled
+pub fn world_bounds(aabb: &Aabb, transform: &GlobalTransform) -> Aabb {
+    let mut min = Vec3::splat(f32::INFINITY);
+    let mut max = Vec3::splat(f32::NEG_INFINITY);
+    for x in [-1.0, 1.0] {
+        for y in [-1.0, 1.0] {
+            for z in [-1.0, 1.0] {
+                let corner =
+                    transform.mul_vec3(aabb.center + aabb.half_extents * Vec3::new(x, y, z));
+                min = min.min(corner);
+                max = max.max(corner);
+            }
+        }
+    }
+
+    Aabb {
+        center: (min + max) / 2.0,
+        half_extents: (max - min) / 2.0,
+    }
+}
+
+/// The smallest bounds around all of them, `None` if there aren't any
+pub fn merge_bounds(bounds: impl Iterator<Item = Aabb>) -> Option<Aabb> {
+    bounds
+        .map(|aabb| {
+            (
+                aabb.center - aabb.half_extents,
+                aabb.center + aabb.half_extents,
+            )
+        })
+        .reduce(|(min_a, max_a), (min_b, max_b)| (min_a.min(min_b), max_a.max(max_b)))
+        .map(|(min, max)| Aabb {
+            center: (min + max) / 2.0,
+            half_extents: (max - min) / 2.0,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn unit_box(center: Vec3) -> Aabb {
+        Aabb {
+            center,
+            half_extents: Vec3::ONE,
+        }
+    }
+
+    #[test]
+    fn rays_hit_the_near_side() {
+        let aabb = unit_box(Vec3::new(0.0, 0.0, -10.0));
+        assert_eq!(ray_distance(Vec3::ZERO, -Vec3::Z, &aabb), Some(9.0));
+    }
+
+    #[test]
+    fn rays_miss_boxes_beside_or_behind_them() {
+        let aabb = unit_box(Vec3::new(5.0, 0.0, -10.0));
+        assert_eq!(ray_distance(Vec3::ZERO, -Vec3::Z, &aabb), None);
+        let behind = unit_box(Vec3::new(0.0, 0.0, 10.0));
+        assert_eq!(ray_distance(Vec3::ZERO, -Vec3::Z, &behind), None);
+    }
+
+    #[test]
+    fn rays_from_inside_hit_straight_away() {
+        let aabb = unit_box(Vec3::ZERO);
+        assert_eq!(ray_distance(Vec3::ZERO, Vec3::X, &aabb), Some(0.0));
+    }
+
+    #[test]
+    fn diagonal_rays_hit() {
+        let aabb = unit_box(Vec3::new(10.0, 10.0, 0.0));
+        let direction = Vec3::new(1.0, 1.0, 0.0).normalize();
+        let distance = ray_distance(Vec3::ZERO, direction, &aabb).unwrap();
+        assert!((distance - 9.0 * 2f32.sqrt()).abs() < 1e-4);
+    }
+
+    #[test]
+    fn axis_aligned_rays_along_a_face_hit() {
+        // the ray runs along the box's top face, so y divides 0 by 0
+        let aabb = unit_box(Vec3::new(0.0, -1.0, -10.0));
+        assert_eq!(ray_distance(Vec3::ZERO, -Vec3::Z, &aabb), Some(9.0));
+        let beside = unit_box(Vec3::new(0.0, -2.5, -10.0));
+        assert_eq!(ray_distance(Vec3::ZERO, -Vec3::Z, &beside), None);
+    }
+}
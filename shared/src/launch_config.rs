@@ -0,0 +1,348 @@
+//! Settings passed in when the app is launched.
+//! On wasm they come from the page's query string like `?seed=42&count=2000`, natively from flags like `--seed 42 --count 2000`.
+//! [`crate::params::RegisterParams::register_params`] applies any that match a parameter's field name,
+//! anything nobody asked for is warned about at startup.
+//!
+//! The config is read before logging is set up, so problems with it are held in [`LaunchConfig::warn`] until they can be logged.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy::prelude::*;
+use bevy::reflect::Struct;
+
+use crate::view_link::decode_component;
+
+/// Adds the [`LaunchConfig`] resource if it isn't there yet, and logs its warnings and unused settings
+pub struct LaunchConfigPlugin;
+
+impl Plugin for LaunchConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.world
+            .get_resource_or_insert_with(LaunchConfig::from_environment);
+        app.add_startup_system_to_stage(StartupStage::PostStartup, warn_unused_config)
+            // applying the config to a reloaded params file can find more problems
+            .add_system_to_stage(CoreStage::Last, report_config_warnings);
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct LaunchConfig {
+    values: BTreeMap<String, String>,
+    used: BTreeSet<String>,
+    warnings: Vec<String>,
+}
+
+impl LaunchConfig {
+    /// Read `key=value` pairs separated by `&`, like a URL query string
+    pub fn parse_query(query: &str) -> Self {
+        let mut config = LaunchConfig::default();
+        for pair in query.trim_start_matches('?').split('&') {
+            if pair.is_empty() {
+                continue;
+            }
+            let (key, value) = pair.split_once('=').unwrap_or((pair, "true"));
+            config.insert(&decode_component(key), decode_component(value));
+        }
+        config
+    }
+
+    /// Read `--key value` and `--key=value` flags, a flag without a value is `true`
+    pub fn parse_args(args: impl IntoIterator<Item = String>) -> Self {
+        let mut config = LaunchConfig::default();
+        let mut args = args.into_iter().peekable();
+        while let Some(arg) = args.next() {
+            let flag = match arg.strip_prefix("--") {
+                Some(flag) => flag,
+                None => {
+                    config.warn(format!("Ignoring unexpected argument {}", arg));
+                    continue;
+                }
+            };
+
+            if let Some((key, value)) = flag.split_once('=') {
+                config.insert(key, value.to_string());
+            } else if args.peek().map_or(false, |next| !next.starts_with("--")) {
+                config.insert(flag, args.next().unwrap());
+            } else {
+                config.insert(flag, "true".to_string());
+            }
+        }
+        config
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_environment() -> Self {
+        Self::parse_args(std::env::args().skip(1))
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    pub fn from_environment() -> Self {
+        let search = web_sys::window().and_then(|window| window.location().search().ok());
+        Self::parse_query(&search.unwrap_or_default())
+    }
+
+    /// `-` and `_` are interchangeable so flags can be written either way
+    fn insert(&mut self, key: &str, value: String) {
+        self.values.insert(key.replace('-', "_"), value);
+    }
+
+    /// Set `key` unless it was passed in already, for settings that come from somewhere less direct like a shared link
+    pub(crate) fn insert_missing(&mut self, key: &str, value: String) {
+        let key = key.replace('-', "_");
+        self.values.entry(key).or_insert(value);
+    }
+
+    /// The raw value for `key`, marking it as used
+    pub fn get(&mut self, key: &str) -> Option<&str> {
+        let key = key.replace('-', "_");
+        let value = self.values.get(&key)?;
+        self.used.insert(key);
+        Some(value)
+    }
+
+    /// Parse the value for `key`, warning and returning `None` if it's malformed
+    pub fn parse<T: std::str::FromStr>(&mut self, key: &str) -> Option<T> {
+        let value = self.get(key)?.to_string();
+        let parsed = value.parse().ok();
+        if parsed.is_none() {
+            self.warn(format!(
+                "Ignoring {}={}, expected a {}",
+                key,
+                value,
+                std::any::type_name::<T>()
+            ));
+        }
+        parsed
+    }
+
+    /// Apply every value matching a field of `params`, by name
+    pub fn apply(&mut self, params: &mut dyn Struct) {
+        for i in 0..params.field_len() {
+            let name = params.name_at(i).unwrap().to_string();
+            let value = match self.get(&name) {
+                Some(value) => value.to_string(),
+                None => continue,
+            };
+            let field = params.field_at_mut(i).unwrap();
+            if let Err(expected) = parse_field(field, &value) {
+                self.warn(format!(
+                    "Ignoring {}={}, expected {}",
+                    name, value, expected
+                ));
+            }
+        }
+    }
+
+    /// Report a problem with a setting, it's logged once logging is set up
+    pub fn warn(&mut self, message: impl Into<String>) {
+        self.warnings.push(message.into());
+    }
+
+    /// Keys that were passed in but never asked for
+    pub fn unused(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.values
+            .iter()
+            .filter(|(key, _)| !self.used.contains(*key))
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+}
+
+/// Set `field` from `value`, returning what was expected if it couldn't be parsed
+fn parse_field(field: &mut dyn Reflect, value: &str) -> Result<(), &'static str> {
+    fn set<T: std::str::FromStr>(field: &mut T, value: &str) -> Result<(), &'static str> {
+        *field = value.parse().map_err(|_| std::any::type_name::<T>())?;
+        Ok(())
+    }
+
+    if let Some(field) = field.downcast_mut::<f32>() {
+        set(field, value)
+    } else if let Some(field) = field.downcast_mut::<f64>() {
+        set(field, value)
+    } else if let Some(field) = field.downcast_mut::<u32>() {
+        set(field, value)
+    } else if let Some(field) = field.downcast_mut::<u64>() {
+        set(field, value)
+    } else if let Some(field) = field.downcast_mut::<i32>() {
+        set(field, value)
+    } else if let Some(field) = field.downcast_mut::<usize>() {
+        set(field, value)
+    } else if let Some(field) = field.downcast_mut::<bool>() {
+        set(field, value)
+    } else if let Some(field) = field.downcast_mut::<String>() {
+        *field = value.to_string();
+        Ok(())
+    } else if let Some(field) = field.downcast_mut::<Vec3>() {
+        let values = value
+            .split(',')
+            .map(|value| value.trim().parse::<f32>().ok())
+            .collect::<Option<Vec<_>>>();
+        match values.as_deref() {
+            Some(&[x, y, z]) => {
+                *field = Vec3::new(x, y, z);
+                Ok(())
+            }
+            _ => Err("x,y,z"),
+        }
+    } else if let Some(field) = field.downcast_mut::<Color>() {
+        *field = Color::hex(value.trim_start_matches('#')).map_err(|_| "a hex color")?;
+        Ok(())
+    } else {
+        Err("a type that can't be set from the launch config")
+    }
+}
+
+/// Write `field` in the form [`parse_field`] reads, `None` for types it can't read
+pub(crate) fn format_field(field: &dyn Reflect) -> Option<String> {
+    if let Some(field) = field.downcast_ref::<f32>() {
+        Some(field.to_string())
+    } else if let Some(field) = field.downcast_ref::<f64>() {
+        Some(field.to_string())
+    } else if let Some(field) = field.downcast_ref::<u32>() {
+        Some(field.to_string())
+    } else if let Some(field) = field.downcast_ref::<u64>() {
+        Some(field.to_string())
+    } else if let Some(field) = field.downcast_ref::<i32>() {
+        Some(field.to_string())
+    } else if let Some(field) = field.downcast_ref::<usize>() {
+        Some(field.to_string())
+    } else if let Some(field) = field.downcast_ref::<bool>() {
+        Some(field.to_string())
+    } else if let Some(field) = field.downcast_ref::<String>() {
+        Some(field.clone())
+    } else if let Some(field) = field.downcast_ref::<Vec3>() {
+        Some(format!("{},{},{}", field.x, field.y, field.z))
+    } else if let Some(field) = field.downcast_ref::<Color>() {
+        let [r, g, b, a] = field
+            .as_rgba_f32()
+            .map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8);
+        Some(format!("{:02x}{:02x}{:02x}{:02x}", r, g, b, a))
+    } else {
+        None
+    }
+}
+
+fn warn_unused_config(mut config: ResMut<LaunchConfig>) {
+    log_warnings(&mut config);
+    for (key, value) in config.unused() {
+        warn!("Ignoring unknown setting {}={}", key, value);
+    }
+}
+
+fn report_config_warnings(mut config: ResMut<LaunchConfig>) {
+    // only borrow mutably when there's something to report
+    if !config.warnings.is_empty() {
+        log_warnings(&mut config);
+    }
+}
+
+fn log_warnings(config: &mut LaunchConfig) {
+    for warning in config.warnings.drain(..) {
+        warn!("{}", warning);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> LaunchConfig {
+        LaunchConfig::parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn args_with_values() {
+        let mut config = args(&["--seed=42", "--count", "2000"]);
+        assert_eq!(config.get("seed"), Some("42"));
+        assert_eq!(config.get("count"), Some("2000"));
+        assert!(config.warnings.is_empty());
+    }
+
+    #[test]
+    fn bare_flags_are_true() {
+        let mut config = args(&["--slideshow", "--export", "frames", "--verbose"]);
+        assert_eq!(config.get("slideshow"), Some("true"));
+        assert_eq!(config.get("export"), Some("frames"));
+        assert_eq!(config.get("verbose"), Some("true"));
+    }
+
+    #[test]
+    fn dashes_and_underscores_match() {
+        let mut config = args(&["--export-fps", "30"]);
+        assert_eq!(config.get("export_fps"), Some("30"));
+        assert_eq!(config.get("export-fps"), Some("30"));
+    }
+
+    #[test]
+    fn later_args_win() {
+        let mut config = args(&["--seed", "1", "--seed=2"]);
+        assert_eq!(config.get("seed"), Some("2"));
+    }
+
+    #[test]
+    fn unexpected_args_are_warned_about() {
+        let mut config = args(&["stray", "--seed", "1"]);
+        assert_eq!(config.get("seed"), Some("1"));
+        assert_eq!(config.warnings.len(), 1);
+        assert!(config.warnings[0].contains("stray"));
+    }
+
+    #[test]
+    fn query_pairs() {
+        let mut config = LaunchConfig::parse_query("?seed=42&&count=2000&slideshow");
+        assert_eq!(config.get("seed"), Some("42"));
+        assert_eq!(config.get("count"), Some("2000"));
+        assert_eq!(config.get("slideshow"), Some("true"));
+    }
+
+    #[test]
+    fn query_is_decoded() {
+        let mut config =
+            LaunchConfig::parse_query("?name=a%20b+c&color=%23ff8000&odd=100%&snow=%E2%9D%84");
+        assert_eq!(config.get("name"), Some("a b c"));
+        assert_eq!(config.get("color"), Some("#ff8000"));
+        assert_eq!(config.get("odd"), Some("100%"));
+        assert_eq!(config.get("snow"), Some("❄"));
+    }
+
+    #[test]
+    fn later_query_pairs_win() {
+        let mut config = LaunchConfig::parse_query("seed=1&seed=2");
+        assert_eq!(config.get("seed"), Some("2"));
+    }
+
+    #[test]
+    fn malformed_values_are_warned_about() {
+        let mut config = LaunchConfig::parse_query("seed=abc");
+        assert_eq!(config.parse::<u64>("seed"), None);
+        assert_eq!(config.warnings.len(), 1);
+    }
+
+    #[test]
+    fn unused_keys() {
+        let mut config = LaunchConfig::parse_query("seed=1&typo=2");
+        config.get("seed");
+        assert_eq!(config.unused().collect::<Vec<_>>(), vec![("typo", "2")]);
+    }
+
+    fn round_trip<T: Reflect + Default>(value: T) -> T {
+        let formatted = format_field(&value).unwrap();
+        let mut parsed = T::default();
+        parse_field(&mut parsed, &formatted).unwrap();
+        parsed
+    }
+
+    #[test]
+    fn formatted_fields_parse_back() {
+        assert_eq!(round_trip(0.1f32), 0.1);
+        assert_eq!(round_trip(2000usize), 2000);
+        assert!(round_trip(true));
+        assert_eq!(
+            round_trip(Vec3::new(1.0, -2.5, 0.1)),
+            Vec3::new(1.0, -2.5, 0.1)
+        );
+        assert_eq!(
+            round_trip(Color::rgba_u8(255, 0, 51, 128)),
+            Color::rgba_u8(255, 0, 51, 128)
+        );
+    }
+}
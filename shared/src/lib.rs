@@ -1 +1,20 @@
+pub mod art_app;
+pub mod art_piece;
+pub mod asset_folders;
+pub mod camera_bookmarks;
+pub mod camera_path;
+pub mod diagnostics_overlay;
+pub mod fly_camera;
+pub mod frame_export;
+pub mod frame_selection;
+pub mod launch_config;
 pub mod pan_orbit_camera;
+pub mod params;
+pub mod params_file;
+pub mod screenshot;
+pub mod seeded_rng;
+pub mod sim_clock;
+pub mod storage;
+pub mod view_link;
+
+pub use art_app::ArtAppPlugin;
@@ -1,16 +1,89 @@
+use std::ops::{Add, Mul, Sub};
+
 use bevy::input::mouse::{MouseMotion, MouseWheel};
 use bevy::prelude::*;
-use bevy::render::camera::PerspectiveProjection;
+use bevy::render::camera::{Camera, OrthographicProjection, PerspectiveProjection};
+use bevy::window::WindowId;
+use serde::{Deserialize, Serialize};
+
+use crate::pan_orbit_camera::auto_rotate::AutoRotate;
+use crate::pan_orbit_camera::constraints::PanOrbitConstraints;
+use crate::pan_orbit_camera::events::{pan_orbit_events, PanOrbitEvent};
+use crate::pan_orbit_camera::gamepad::{gamepad_input, PanOrbitGamepad};
+use crate::pan_orbit_camera::keyboard::{keyboard_input, PanOrbitKeys};
+use crate::pan_orbit_camera::touch::touch_input;
+use crate::sim_clock::{frame_seconds, SimClock};
+
+pub mod auto_rotate;
+pub mod constraints;
+pub mod events;
+pub mod gamepad;
+pub mod keyboard;
+pub mod touch;
 
 // from: https://bevy-cheatbook.github.io/cookbook/pan-orbit-camera.html
 
+/// Adds the [`PanOrbitSettings`] resource, the mouse, touch, keyboard and gamepad input systems, the [`PanOrbitEvent`] event and the [`pan_orbit_camera`] system.
+pub struct PanOrbitCameraPlugin;
+
+impl Plugin for PanOrbitCameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PanOrbitSettings>()
+            .init_resource::<PanOrbitInput>()
+            .add_event::<PanOrbitEvent>()
+            .add_system_to_stage(CoreStage::PreUpdate, init_pan_orbit_camera)
+            .add_system(mouse_input.label(PanOrbitSystem::Input))
+            .add_system(touch_input.label(PanOrbitSystem::Input))
+            .add_system(keyboard_input.label(PanOrbitSystem::Input))
+            .add_system(gamepad_input.label(PanOrbitSystem::Input))
+            .add_system(
+                animate_pan_orbit_transition
+                    .after(PanOrbitSystem::Input)
+                    .before(PanOrbitSystem::Camera),
+            )
+            .add_system(
+                pan_orbit_events
+                    .after(PanOrbitSystem::Input)
+                    .before(PanOrbitSystem::Camera),
+            )
+            .add_system(
+                pan_orbit_camera
+                    .label(PanOrbitSystem::Camera)
+                    .after(PanOrbitSystem::Input),
+            );
+
+        // bevy's own gilrs feature would turn it on for wasm too, where it breaks Firefox
+        #[cfg(all(feature = "gamepad", not(target_arch = "wasm32")))]
+        app.add_plugin(bevy_gilrs::GilrsPlugin);
+    }
+}
+
+#[derive(SystemLabel, Clone, Hash, Debug, PartialEq, Eq)]
+pub enum PanOrbitSystem {
+    /// Systems that write to [`PanOrbitInput`]
+    Input,
+    /// Applies the input to the cameras, anything that changes a [`PanOrbitCamera`] target should run before this
+    Camera,
+}
+
 /// Tags an entity as capable of panning and orbiting.
-#[derive(Component)]
+/// These are the target values, the camera's [`Transform`] eases towards them based on [`PanOrbitSettings::smoothing`].
+/// `yaw` and `pitch` are initialized from the `Transform` when the camera is spawned.
+/// Changing them from code moves the camera on the next frame, or send a [`PanOrbitEvent`].
+#[derive(Component, Clone, Debug)]
 pub struct PanOrbitCamera {
     /// The "focus point" to orbit around. It is automatically updated when panning the camera
     pub focus: Vec3,
     pub radius: f32,
+    /// Rotation around the global y axis in radians
+    pub yaw: f32,
+    /// Rotation around the local x axis in radians
+    pub pitch: f32,
     pub upside_down: bool,
+    /// Ignore input and leave the `Transform` alone, so something else can drive the camera
+    pub enabled: bool,
+    /// Slowly orbit the focus when nobody is using the camera
+    pub auto_rotate: Option<AutoRotate>,
 }
 
 impl Default for PanOrbitCamera {
@@ -18,100 +91,728 @@ impl Default for PanOrbitCamera {
         PanOrbitCamera {
             focus: Vec3::ZERO,
             radius: 5.0,
+            yaw: 0.0,
+            pitch: 0.0,
             upside_down: false,
+            enabled: true,
+            auto_rotate: None,
         }
     }
 }
 
-/// Pan the camera with middle mouse click, zoom with scroll wheel, orbit with right mouse click.
-pub fn pan_orbit_camera(
+impl PanOrbitCamera {
+    pub fn view(&self) -> OrbitView {
+        OrbitView {
+            focus: self.focus,
+            radius: self.radius,
+            yaw: self.yaw,
+            pitch: self.pitch,
+        }
+    }
+
+    pub fn set_view(&mut self, view: OrbitView) {
+        self.focus = view.focus;
+        self.radius = view.radius;
+        self.yaw = view.yaw;
+        self.pitch = view.pitch;
+    }
+
+    /// Check whether horizontal orbiting has to be inverted, there's no need when the constraints keep the camera upright
+    pub(crate) fn update_upside_down(&mut self, constraints: Option<&PanOrbitConstraints>) {
+        if !constraints.map_or(false, PanOrbitConstraints::keeps_upright) {
+            self.upside_down = self.pitch.cos() <= 0.0;
+        }
+    }
+}
+
+/// Where a [`PanOrbitCamera`] is looking from
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct OrbitView {
+    pub focus: Vec3,
+    pub radius: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+impl OrbitView {
+    /// Work out the view for a camera at `transform` that is `radius` away from what it's looking at
+    pub fn from_transform(transform: &Transform, radius: f32) -> Self {
+        let offset = transform.rotation * Vec3::Z;
+        OrbitView {
+            focus: transform.translation - offset * radius,
+            radius,
+            yaw: offset.x.atan2(offset.z),
+            pitch: -offset.y.clamp(-1.0, 1.0).asin(),
+        }
+    }
+
+    pub fn rotation(&self) -> Quat {
+        // emulating parent/child to make the yaw/y-axis rotation behave like a turntable
+        // parent = y rotation
+        // child = x rotation
+        Quat::from_rotation_y(self.yaw) * Quat::from_rotation_x(self.pitch)
+    }
+
+    pub fn translation(&self) -> Vec3 {
+        self.focus + self.rotation() * Vec3::new(0.0, 0.0, self.radius)
+    }
+
+    /// Point `transform` at the focus from this view, leaving its scale alone
+    pub fn apply_to(&self, transform: &mut Transform) {
+        transform.rotation = self.rotation();
+        transform.translation = self.translation();
+    }
+
+    pub fn lerp(&self, other: &OrbitView, t: f32) -> OrbitView {
+        *self + (*other - *self) * t
+    }
+
+    /// Check if every value is within `epsilon` of `other`, the focus is compared relative to the radius
+    pub fn abs_diff_eq(&self, other: &OrbitView, epsilon: f32) -> bool {
+        self.focus
+            .abs_diff_eq(other.focus, epsilon * self.radius.max(1.0))
+            && (self.radius - other.radius).abs() <= epsilon * self.radius.max(1.0)
+            && (self.yaw - other.yaw).abs() <= epsilon
+            && (self.pitch - other.pitch).abs() <= epsilon
+    }
+}
+
+impl Add for OrbitView {
+    type Output = OrbitView;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        OrbitView {
+            focus: self.focus + rhs.focus,
+            radius: self.radius + rhs.radius,
+            yaw: self.yaw + rhs.yaw,
+            pitch: self.pitch + rhs.pitch,
+        }
+    }
+}
+
+impl Sub for OrbitView {
+    type Output = OrbitView;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        OrbitView {
+            focus: self.focus - rhs.focus,
+            radius: self.radius - rhs.radius,
+            yaw: self.yaw - rhs.yaw,
+            pitch: self.pitch - rhs.pitch,
+        }
+    }
+}
+
+impl Mul<f32> for OrbitView {
+    type Output = OrbitView;
+
+    fn mul(self, rhs: f32) -> Self::Output {
+        OrbitView {
+            focus: self.focus * rhs,
+            radius: self.radius * rhs,
+            yaw: self.yaw * rhs,
+            pitch: self.pitch * rhs,
+        }
+    }
+}
+
+/// Animates a [`PanOrbitCamera`] to a new view, swinging around the focus instead of cutting straight there.
+/// The transition is removed when it finishes or as soon as the user moves the camera.
+#[derive(Component, Clone, Debug)]
+pub struct PanOrbitTransition {
+    pub from: OrbitView,
+    pub to: OrbitView,
+    /// An orthographic camera's projection scale to zoom from and to, see [`PanOrbitTransition::with_scale`]
+    pub scale: Option<(f32, f32)>,
+    pub duration: f32,
+    pub elapsed: f32,
+}
+
+impl PanOrbitTransition {
+    pub fn new(from: OrbitView, mut to: OrbitView, duration: f32) -> Self {
+        // go the short way around
+        let turns = ((to.yaw - from.yaw) / std::f32::consts::TAU).round();
+        to.yaw -= turns * std::f32::consts::TAU;
+
+        PanOrbitTransition {
+            from,
+            to,
+            scale: None,
+            duration,
+            elapsed: 0.0,
+        }
+    }
+
+    /// Also zoom an orthographic camera, which doesn't zoom by moving closer, from one projection scale to another
+    pub fn with_scale(mut self, from: f32, to: f32) -> Self {
+        self.scale = Some((from, to));
+        self
+    }
+
+    pub fn finished(&self) -> bool {
+        self.elapsed >= self.duration
+    }
+
+    /// How far through the transition it is, from 0 to 1
+    fn progress(&self) -> f32 {
+        if self.finished() {
+            return 1.0;
+        }
+
+        let t = self.elapsed / self.duration;
+        // smoothstep so we ease in and out
+        t * t * (3.0 - 2.0 * t)
+    }
+
+    /// The view `elapsed` seconds into the transition
+    pub fn view(&self) -> OrbitView {
+        if self.finished() {
+            return self.to;
+        }
+
+        let t = self.progress();
+        let mut view = self.from.lerp(&self.to, t);
+        // zoom geometrically so it feels the same speed close up and far away
+        view.radius = zoom_between(self.from.radius, self.to.radius, t);
+        view
+    }
+
+    /// The orthographic scale `elapsed` seconds into the transition, if it changes one
+    pub fn scale(&self) -> Option<f32> {
+        self.scale
+            .map(|(from, to)| zoom_between(from, to, self.progress()))
+    }
+}
+
+/// Geometric interpolation between two distances or scales, falling back to linear if either isn't positive
+fn zoom_between(from: f32, to: f32, t: f32) -> f32 {
+    if from > 0.0 && to > 0.0 {
+        from * (to / from).powf(t)
+    } else {
+        from + (to - from) * t
+    }
+}
+
+/// The view the camera is actually showing while it eases towards its [`PanOrbitCamera`] target,
+/// added automatically to every `PanOrbitCamera`.
+#[derive(Component, Debug, Default)]
+pub struct PanOrbitState {
+    pub current: OrbitView,
+    /// How quickly the target is changing per second, this keeps it moving after input stops
+    pub velocity: OrbitView,
+    /// Seconds since the user last moved this camera
+    pub idle: f32,
+}
+
+impl PanOrbitState {
+    /// Jump straight to `view` without easing or drifting, for taking back over after something else moved the camera
+    pub fn snap(
+        &mut self,
+        pan_orbit: &mut PanOrbitCamera,
+        view: OrbitView,
+        constraints: Option<&PanOrbitConstraints>,
+    ) {
+        pan_orbit.set_view(view);
+        pan_orbit.update_upside_down(constraints);
+        self.current = view;
+        self.velocity = OrbitView::default();
+        self.idle = 0.0;
+    }
+}
+
+/// Input bindings and tuning for every [`PanOrbitCamera`], insert this resource to override the defaults.
+#[derive(Clone, Debug)]
+pub struct PanOrbitSettings {
+    pub orbit_button: MouseButton,
+    /// A key that must also be held down to orbit
+    pub orbit_modifier: Option<KeyCode>,
+    pub pan_button: MouseButton,
+    /// A key that must also be held down to pan, this lets orbit and pan share a button
+    pub pan_modifier: Option<KeyCode>,
+
+    /// Multiplier applied to mouse motion while orbiting
+    pub orbit_sensitivity: f32,
+    /// Multiplier applied to mouse motion while panning
+    pub pan_sensitivity: f32,
+    /// The fraction of the radius to zoom per unit of scroll
+    pub zoom_sensitivity: f32,
+
+    pub invert_x: bool,
+    pub invert_y: bool,
+    pub invert_zoom: bool,
+
+    /// Don't allow zoom to reach zero or you get stuck.
+    /// A camera's [`PanOrbitConstraints`] can override these, see [`PanOrbitSettings::clamp_radius`]
+    pub min_radius: f32,
+    pub max_radius: f32,
+    /// Orthographic cameras zoom by changing their projection's scale instead of the radius
+    pub min_scale: f32,
+    pub max_scale: f32,
+
+    /// Keyboard navigation, `None` disables it
+    pub keyboard: Option<PanOrbitKeys>,
+    /// Gamepad navigation, `None` disables it
+    pub gamepad: Option<PanOrbitGamepad>,
+
+    /// Roughly how many seconds the camera takes to catch up with its target, 0 snaps straight to it
+    pub smoothing: f32,
+    /// Roughly how many seconds the camera keeps drifting after input stops, 0 stops dead
+    pub momentum: f32,
+}
+
+impl Default for PanOrbitSettings {
+    fn default() -> Self {
+        PanOrbitSettings {
+            orbit_button: MouseButton::Left,
+            orbit_modifier: None,
+            pan_button: MouseButton::Middle,
+            pan_modifier: None,
+            orbit_sensitivity: 1.0,
+            pan_sensitivity: 1.0,
+            zoom_sensitivity: 0.01,
+            invert_x: false,
+            invert_y: false,
+            invert_zoom: false,
+            min_radius: 0.05,
+            max_radius: f32::INFINITY,
+            min_scale: 0.001,
+            max_scale: f32::INFINITY,
+            keyboard: Some(PanOrbitKeys::default()),
+            gamepad: Some(PanOrbitGamepad::default()),
+            smoothing: 0.1,
+            momentum: 0.2,
+        }
+    }
+}
+
+impl PanOrbitSettings {
+    fn binding_pressed(
+        button: MouseButton,
+        modifier: Option<KeyCode>,
+        input_mouse: &Input<MouseButton>,
+        input_keyboard: &Input<KeyCode>,
+    ) -> bool {
+        input_mouse.pressed(button) && modifier.map_or(true, |key| input_keyboard.pressed(key))
+    }
+
+    fn orbit_pressed(
+        &self,
+        input_mouse: &Input<MouseButton>,
+        input_keyboard: &Input<KeyCode>,
+    ) -> bool {
+        let orbit = Self::binding_pressed(
+            self.orbit_button,
+            self.orbit_modifier,
+            input_mouse,
+            input_keyboard,
+        );
+
+        // when both bindings match, the one with a modifier is the more specific one
+        orbit && !(self.pan_modifier.is_some() && self.pan_pressed(input_mouse, input_keyboard))
+    }
+
+    fn pan_pressed(
+        &self,
+        input_mouse: &Input<MouseButton>,
+        input_keyboard: &Input<KeyCode>,
+    ) -> bool {
+        Self::binding_pressed(
+            self.pan_button,
+            self.pan_modifier,
+            input_mouse,
+            input_keyboard,
+        )
+    }
+
+    fn invert(&self) -> Vec2 {
+        Vec2::new(
+            if self.invert_x { -1.0 } else { 1.0 },
+            if self.invert_y { -1.0 } else { 1.0 },
+        )
+    }
+
+    /// Convert a screen space movement in pixels into radians to yaw and pitch by,
+    /// dragging across the whole window is a full rotation
+    pub fn orbit_delta(&self, delta: Vec2, window: Vec2) -> Vec2 {
+        let radians = delta / window * Vec2::new(std::f32::consts::PI * 2.0, std::f32::consts::PI);
+        radians * self.invert() * self.orbit_sensitivity
+    }
+
+    /// Convert a screen space movement in pixels into a fraction of the view to pan by
+    pub fn pan_delta(&self, delta: Vec2, window: Vec2) -> Vec2 {
+        delta / window * self.invert() * self.pan_sensitivity
+    }
+
+    /// Keep `radius` within the camera's radius limits, its `constraints` override these settings' where they have their own
+    pub fn clamp_radius(&self, radius: f32, constraints: Option<&PanOrbitConstraints>) -> f32 {
+        let min = constraints
+            .and_then(|constraints| constraints.min_radius)
+            .unwrap_or(self.min_radius);
+        let max = constraints
+            .and_then(|constraints| constraints.max_radius)
+            .unwrap_or(self.max_radius);
+        radius.max(min).min(max)
+    }
+
+    /// Convert scroll units into a fraction of the radius to zoom in by
+    pub fn zoom_delta(&self, scroll: f32) -> f32 {
+        let zoom = scroll * self.zoom_sensitivity;
+        if self.invert_zoom {
+            -zoom
+        } else {
+            zoom
+        }
+    }
+}
+
+/// The camera movement requested this frame, accumulated by every input system.
+/// It's only applied to cameras rendering to the focused `window`.
+#[derive(Default, Debug)]
+pub struct PanOrbitInput {
+    pub window: Option<WindowId>,
+    /// Radians to yaw (x) and pitch (y) by
+    pub orbit: Vec2,
+    /// The fraction of the view to pan by, in screen space
+    pub pan: Vec2,
+    /// The fraction of the radius to zoom in by
+    pub zoom: f32,
+    /// Orbiting started or ended this frame
+    pub orbit_changed: bool,
+    /// The user is holding the camera, even if it didn't move, so it shouldn't drift
+    pub held: bool,
+}
+
+impl PanOrbitInput {
+    const NONE: PanOrbitInput = PanOrbitInput {
+        window: None,
+        orbit: Vec2::ZERO,
+        pan: Vec2::ZERO,
+        zoom: 0.0,
+        orbit_changed: false,
+        held: false,
+    };
+}
+
+/// Orbit with left mouse click, pan with middle mouse click and zoom with the scroll wheel.
+/// The bindings can be changed with [`PanOrbitSettings`].
+pub fn mouse_input(
     windows: Res<Windows>,
+    settings: Res<PanOrbitSettings>,
     mut ev_motion: EventReader<MouseMotion>,
     mut ev_scroll: EventReader<MouseWheel>,
     input_mouse: Res<Input<MouseButton>>,
-    mut query: Query<(&mut PanOrbitCamera, &mut Transform, &PerspectiveProjection)>,
+    input_keyboard: Res<Input<KeyCode>>,
+    mut input: ResMut<PanOrbitInput>,
 ) {
-    // change input mapping for orbit and panning here
-    let orbit_button = MouseButton::Left;
-    let pan_button = MouseButton::Middle;
-
-    let mut pan = Vec2::ZERO;
-    let mut rotation_move = Vec2::ZERO;
-    let mut scroll = 0.0;
-    let mut orbit_button_changed = false;
+    let (window_id, window) = match focused_window(&windows) {
+        Some(focused) => focused,
+        None => return,
+    };
+    input.window = Some(window_id);
 
-    if input_mouse.pressed(orbit_button) {
+    if settings.orbit_pressed(&input_mouse, &input_keyboard) {
         for ev in ev_motion.iter() {
-            rotation_move += ev.delta;
+            input.orbit += settings.orbit_delta(ev.delta, window);
         }
-    } else if input_mouse.pressed(pan_button) {
+    } else if settings.pan_pressed(&input_mouse, &input_keyboard) {
         // Pan only if we're not rotating at the moment
         for ev in ev_motion.iter() {
-            pan += ev.delta;
+            input.pan += settings.pan_delta(ev.delta, window);
         }
     }
     for ev in ev_scroll.iter() {
-        scroll += ev.y;
+        input.zoom += settings.zoom_delta(ev.y);
     }
-    if input_mouse.just_released(orbit_button) || input_mouse.just_pressed(orbit_button) {
-        orbit_button_changed = true;
+    if input_mouse.just_released(settings.orbit_button)
+        || input_mouse.just_pressed(settings.orbit_button)
+    {
+        input.orbit_changed = true;
+    }
+    if settings.orbit_pressed(&input_mouse, &input_keyboard)
+        || settings.pan_pressed(&input_mouse, &input_keyboard)
+    {
+        input.held = true;
+    }
+}
+
+/// Start new cameras from wherever their `Transform` is pointing and add their [`PanOrbitState`]
+pub fn init_pan_orbit_camera(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut PanOrbitCamera, &mut Transform), Added<PanOrbitCamera>>,
+) {
+    for (entity, mut pan_orbit, mut transform) in query.iter_mut() {
+        let view = OrbitView::from_transform(&transform, pan_orbit.radius);
+        pan_orbit.yaw = view.yaw;
+        pan_orbit.pitch = view.pitch;
+
+        let current = pan_orbit.view();
+        current.apply_to(&mut transform);
+        commands.entity(entity).insert(PanOrbitState {
+            current,
+            velocity: OrbitView::default(),
+            idle: 0.0,
+        });
     }
+}
+
+/// Move cameras along their [`PanOrbitTransition`], handing control back to the user on any input
+#[allow(clippy::type_complexity)]
+pub fn animate_pan_orbit_transition(
+    mut commands: Commands,
+    time: Res<Time>,
+    clock: Option<Res<SimClock>>,
+    input: Res<PanOrbitInput>,
+    mut query: Query<(
+        Entity,
+        &mut PanOrbitCamera,
+        &mut PanOrbitState,
+        &mut PanOrbitTransition,
+        Option<&mut OrthographicProjection>,
+        Option<&PanOrbitConstraints>,
+    )>,
+) {
+    let interrupted =
+        input.held || input.orbit != Vec2::ZERO || input.pan != Vec2::ZERO || input.zoom != 0.0;
 
-    for (mut pan_orbit, mut transform, projection) in query.iter_mut() {
-        if orbit_button_changed {
+    for (entity, mut pan_orbit, mut state, mut transition, orthographic, constraints) in
+        query.iter_mut()
+    {
+        if !pan_orbit.enabled {
+            continue;
+        }
+        if interrupted {
+            commands.entity(entity).remove::<PanOrbitTransition>();
+            continue;
+        }
+
+        transition.elapsed += frame_seconds(&time, clock.as_deref());
+        pan_orbit.set_view(transition.view());
+        pan_orbit.update_upside_down(constraints);
+        if let (Some(mut orthographic), Some(scale)) = (orthographic, transition.scale()) {
+            orthographic.scale = scale;
+        }
+        // don't let any leftover momentum fight the animation
+        state.velocity = OrbitView::default();
+
+        if transition.finished() {
+            commands.entity(entity).remove::<PanOrbitTransition>();
+        }
+    }
+}
+
+/// Apply the accumulated [`PanOrbitInput`] to the [`PanOrbitCamera`]s in the focused window and ease every camera towards its target.
+#[allow(clippy::type_complexity)]
+pub fn pan_orbit_camera(
+    time: Res<Time>,
+    clock: Option<Res<SimClock>>,
+    settings: Res<PanOrbitSettings>,
+    mut input: ResMut<PanOrbitInput>,
+    mut query: Query<(
+        &mut PanOrbitCamera,
+        &mut PanOrbitState,
+        &mut Transform,
+        &Camera,
+        Option<&PerspectiveProjection>,
+        Option<&mut OrthographicProjection>,
+        Option<&PanOrbitConstraints>,
+    )>,
+) {
+    let delta_seconds = frame_seconds(&time, clock.as_deref());
+
+    for (mut pan_orbit, mut state, mut transform, camera, perspective, orthographic, constraints) in
+        query.iter_mut()
+    {
+        if !pan_orbit.enabled {
+            continue;
+        }
+
+        let input = if input.window == Some(camera.window) {
+            &*input
+        } else {
+            &PanOrbitInput::NONE
+        };
+
+        if input.orbit_changed {
             // only check for upside down when orbiting started or ended this frame
             // if the camera is "upside" down, panning horizontally would be inverted, so invert the input to make it correct
-            let up = transform.rotation * Vec3::Y;
-            pan_orbit.upside_down = up.y <= 0.0;
+            pan_orbit.update_upside_down(constraints);
         }
 
+        let previous_target = pan_orbit.view();
         let mut any = false;
-        if rotation_move.length_squared() > 0.0 {
-            any = true;
-            let window = get_primary_window_size(&windows);
-            let delta_x = {
-                let delta = rotation_move.x / window.x * std::f32::consts::PI * 2.0;
-                if pan_orbit.upside_down {
-                    -delta
-                } else {
-                    delta
-                }
-            };
-            let delta_y = rotation_move.y / window.y * std::f32::consts::PI;
-            let yaw = Quat::from_rotation_y(-delta_x);
-            let pitch = Quat::from_rotation_x(-delta_y);
-            transform.rotation *= yaw; // rotate around global y axis
-            transform.rotation *= pitch; // rotate around local x axis
-        } else if pan.length_squared() > 0.0 {
+        if input.orbit.length_squared() > 0.0 {
             any = true;
-            // make panning distance independent of resolution and FOV,
-            let window = get_primary_window_size(&windows);
-            pan *= Vec2::new(projection.fov * projection.aspect_ratio, projection.fov) / window;
-            // translate by local axes
-            let right = transform.rotation * Vec3::X * -pan.x;
-            let up = transform.rotation * Vec3::Y * pan.y;
-            // make panning proportional to distance away from focus point
-            let translation = (right + up) * pan_orbit.radius;
-            pan_orbit.focus += translation;
-        } else if scroll.abs() > 0.0 {
+            orbit(&mut pan_orbit, input.orbit);
+        }
+        if input.pan.length_squared() > 0.0 {
             any = true;
-            pan_orbit.radius -= scroll * pan_orbit.radius * 0.01;
-            // dont allow zoom to reach zero or you get stuck
-            pan_orbit.radius = f32::max(pan_orbit.radius, 0.05);
+            let view_size = match (perspective, orthographic.as_deref()) {
+                (Some(perspective), _) => perspective_view_size(perspective, pan_orbit.radius),
+                (None, Some(orthographic)) => orthographic_view_size(orthographic),
+                // some other projection, treat the view as a unit square
+                (None, None) => Vec2::ONE,
+            };
+            pan(&mut pan_orbit, input.pan, view_size);
+        }
+        if input.zoom.abs() > 0.0 {
+            match orthographic {
+                Some(mut orthographic) => {
+                    zoom_orthographic(&mut orthographic, input.zoom, &settings)
+                }
+                None => {
+                    any = true;
+                    zoom(&mut pan_orbit, input.zoom, &settings, constraints);
+                }
+            }
         }
 
         if any {
-            // emulating parent/child to make the yaw/y-axis rotation behave like a turntable
-            // parent = x and y rotation
-            // child = z-offset
-            let rot_matrix = Mat3::from_quat(transform.rotation);
-            transform.translation =
-                pan_orbit.focus + rot_matrix.mul_vec3(Vec3::new(0.0, 0.0, pan_orbit.radius));
+            if delta_seconds > 0.0 {
+                state.velocity = (pan_orbit.view() - previous_target) * (1.0 / delta_seconds);
+            }
+        } else if input.held || settings.momentum <= 0.0 {
+            state.velocity = OrbitView::default();
+        } else if state.velocity != OrbitView::default() {
+            // keep drifting after the input stops, slowing down exponentially so it's the same at any frame rate
+            let drift = pan_orbit.view() + state.velocity * delta_seconds;
+            pan_orbit.set_view(drift);
+            pan_orbit.radius = settings.clamp_radius(pan_orbit.radius, constraints);
+            state.velocity = state.velocity * (-delta_seconds / settings.momentum).exp();
+            if state.velocity.abs_diff_eq(&OrbitView::default(), 1e-4) {
+                state.velocity = OrbitView::default();
+            }
+        }
+
+        let idle = state.idle;
+        state.idle = if any || input.held || input.zoom != 0.0 {
+            0.0
+        } else {
+            idle + delta_seconds
+        };
+        if let Some(auto_rotate) = pan_orbit.auto_rotate {
+            if state.idle > auto_rotate.idle_timeout {
+                let mut view = pan_orbit.view();
+                auto_rotate.turn(
+                    &mut view,
+                    (idle - auto_rotate.idle_timeout).max(0.0),
+                    state.idle - auto_rotate.idle_timeout,
+                );
+                pan_orbit.set_view(view);
+            }
+        }
+
+        // transitions can ask for any radius, so this is checked every frame
+        let mut constrained = pan_orbit.view();
+        constrained.radius = settings.clamp_radius(constrained.radius, constraints);
+        if let Some(constraints) = constraints {
+            constraints.apply(&mut constrained);
+        }
+        if constrained != pan_orbit.view() {
+            pan_orbit.set_view(constrained);
+        }
+
+        let target = pan_orbit.view();
+        if state.current != target {
+            state.current = if settings.smoothing > 0.0 {
+                let t = 1.0 - (-delta_seconds / settings.smoothing).exp();
+                let eased = state.current.lerp(&target, t);
+                if eased.abs_diff_eq(&target, 1e-4) {
+                    target
+                } else {
+                    eased
+                }
+            } else {
+                target
+            };
+            // easing between two good views can still cut through the middle of something
+            if let Some(keep_out) = constraints.and_then(|constraints| constraints.keep_out) {
+                keep_out.push_out(&mut state.current);
+            }
+            state.current.apply_to(&mut transform);
         }
     }
+
+    *input = PanOrbitInput::default();
+}
+
+/// Rotate around the focus point by radians of yaw and pitch
+fn orbit(pan_orbit: &mut PanOrbitCamera, delta: Vec2) {
+    let delta_x = if pan_orbit.upside_down {
+        -delta.x
+    } else {
+        delta.x
+    };
+    pan_orbit.yaw -= delta_x;
+    pan_orbit.pitch -= delta.y;
+}
+
+/// The world space width and height visible at the focus point,
+/// making panning proportional to distance away from focus point
+fn perspective_view_size(projection: &PerspectiveProjection, radius: f32) -> Vec2 {
+    Vec2::new(projection.fov * projection.aspect_ratio, projection.fov) * radius
+}
+
+/// The world space width and height visible, which doesn't depend on the distance
+fn orthographic_view_size(projection: &OrthographicProjection) -> Vec2 {
+    Vec2::new(
+        projection.right - projection.left,
+        projection.top - projection.bottom,
+    ) * projection.scale
+}
+
+/// Move the focus point so it tracks a screen space movement, measured as a fraction of the view
+fn pan(pan_orbit: &mut PanOrbitCamera, delta: Vec2, view_size: Vec2) {
+    // make panning distance independent of resolution and FOV,
+    let delta = delta * view_size;
+    // translate by local axes
+    let rotation = pan_orbit.view().rotation();
+    let right = rotation * Vec3::X * -delta.x;
+    let up = rotation * Vec3::Y * delta.y;
+    pan_orbit.focus += right + up;
+}
+
+/// Move towards the focus point by a fraction of the radius
+fn zoom(
+    pan_orbit: &mut PanOrbitCamera,
+    zoom: f32,
+    settings: &PanOrbitSettings,
+    constraints: Option<&PanOrbitConstraints>,
+) {
+    pan_orbit.radius -= zoom * pan_orbit.radius;
+    pan_orbit.radius = settings.clamp_radius(pan_orbit.radius, constraints);
 }
 
-fn get_primary_window_size(windows: &Res<Windows>) -> Vec2 {
-    let window = windows.get_primary().unwrap();
-    Vec2::new(window.width() as f32, window.height() as f32)
+/// Shrink the visible area by a fraction, moving an orthographic camera closer doesn't change what it sees
+fn zoom_orthographic(
+    projection: &mut OrthographicProjection,
+    zoom: f32,
+    settings: &PanOrbitSettings,
+) {
+    projection.scale -= zoom * projection.scale;
+    projection.scale = projection
+        .scale
+        .clamp(settings.min_scale, settings.max_scale);
+}
+
+/// The window that input is going to and its size
+pub(crate) fn focused_window(windows: &Windows) -> Option<(WindowId, Vec2)> {
+    let mut all = windows.iter();
+    let window = windows
+        .iter()
+        .find(|window| window.is_focused())
+        .or_else(|| {
+            // focus isn't always reported (like on wasm), but with a single window the input has to be for it
+            match (all.next(), all.next()) {
+                (Some(only), None) => Some(only),
+                _ => None,
+            }
+        })?;
+
+    let size = Vec2::new(window.width(), window.height());
+    // a minimized window has no size to scale the input by
+    if size.x > 0.0 && size.y > 0.0 {
+        Some((window.id(), size))
+    } else {
+        None
+    }
 }
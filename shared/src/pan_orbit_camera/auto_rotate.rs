@@ -0,0 +1,58 @@
+use std::f32::consts::{PI, TAU};
+
+use bevy::prelude::*;
+
+use crate::pan_orbit_camera::OrbitView;
+
+/// Turntable mode for gallery displays, the camera orbits on its own once nobody has touched it for a while
+#[derive(Clone, Copy, Debug)]
+pub struct AutoRotate {
+    /// Radians per second, positive turns counter-clockwise around `axis`
+    pub speed: f32,
+    /// The axis through the focus point to orbit around
+    pub axis: Vec3,
+    /// Seconds without any input before the camera starts turning
+    pub idle_timeout: f32,
+    /// How far to bob up and down in radians, 0 turns it off
+    pub bob_amplitude: f32,
+    /// Seconds for one bob up and down
+    pub bob_period: f32,
+}
+
+impl Default for AutoRotate {
+    fn default() -> Self {
+        AutoRotate {
+            speed: 0.1,
+            axis: Vec3::Y,
+            idle_timeout: 30.0,
+            bob_amplitude: 0.0,
+            bob_period: 20.0,
+        }
+    }
+}
+
+impl AutoRotate {
+    /// Turn `view` by however far the turntable moves between `from` and `to` seconds after it started
+    pub fn turn(&self, view: &mut OrbitView, from: f32, to: f32) {
+        let angle = self.speed * (to - from);
+        let axis = self.axis.normalize_or_zero();
+
+        if axis.abs_diff_eq(Vec3::Y, 1e-6) {
+            // the common case where our yaw already turns around the axis
+            view.yaw += angle;
+        } else if axis.abs_diff_eq(-Vec3::Y, 1e-6) {
+            view.yaw -= angle;
+        } else if axis != Vec3::ZERO {
+            let direction = Quat::from_axis_angle(axis, angle) * (view.rotation() * Vec3::Z);
+            let yaw = direction.x.atan2(direction.z);
+            // keep yaw continuous so the easing doesn't spin the long way around
+            view.yaw += (yaw - view.yaw + PI).rem_euclid(TAU) - PI;
+            view.pitch = -direction.y.clamp(-1.0, 1.0).asin();
+        }
+
+        if self.bob_amplitude != 0.0 && self.bob_period > 0.0 {
+            let bob = |seconds: f32| self.bob_amplitude * (seconds / self.bob_period * TAU).sin();
+            view.pitch += bob(to) - bob(from);
+        }
+    }
+}
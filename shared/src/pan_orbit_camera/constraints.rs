@@ -0,0 +1,101 @@
+use bevy::prelude::*;
+
+use crate::pan_orbit_camera::OrbitView;
+
+/// Optional limits on where a [`PanOrbitCamera`](crate::pan_orbit_camera::PanOrbitCamera) can go.
+#[derive(Component, Clone, Debug, Default)]
+pub struct PanOrbitConstraints {
+    /// Radians, keeping this within ±π/2 means the camera can never go over the top and end up upside down
+    pub min_pitch: Option<f32>,
+    pub max_pitch: Option<f32>,
+    /// Replace the radius limits in [`PanOrbitSettings`](crate::pan_orbit_camera::PanOrbitSettings) for this camera,
+    /// `None` keeps the settings' limit
+    pub min_radius: Option<f32>,
+    pub max_radius: Option<f32>,
+    /// The focus point has to stay inside this
+    pub focus_bounds: Option<Bounds>,
+    /// The camera has to stay outside this, it wins over `max_radius`
+    pub keep_out: Option<KeepOut>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Bounds {
+    Box { min: Vec3, max: Vec3 },
+    Sphere { center: Vec3, radius: f32 },
+}
+
+impl Bounds {
+    pub fn clamp(&self, point: Vec3) -> Vec3 {
+        match *self {
+            Bounds::Box { min, max } => point.clamp(min, max),
+            Bounds::Sphere { center, radius } => center + (point - center).clamp_length_max(radius),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum KeepOut {
+    Sphere {
+        center: Vec3,
+        radius: f32,
+    },
+    /// A signed distance function for the volume and how far outside of it the camera has to stay
+    Sdf {
+        distance: fn(Vec3) -> f32,
+        margin: f32,
+    },
+}
+
+impl KeepOut {
+    fn distance(&self, point: Vec3) -> f32 {
+        match *self {
+            KeepOut::Sphere { center, radius } => point.distance(center) - radius,
+            KeepOut::Sdf { distance, margin } => distance(point) - margin,
+        }
+    }
+
+    /// Back the camera away from the focus until it's outside the volume
+    pub fn push_out(&self, view: &mut OrbitView) {
+        const MAX_STEPS: usize = 64;
+
+        let direction = view.rotation() * Vec3::Z;
+        for _ in 0..MAX_STEPS {
+            let distance = self.distance(view.focus + direction * view.radius);
+            if distance >= 0.0 {
+                break;
+            }
+            // a distance function never overestimates, so stepping by it can't skip past the surface
+            view.radius += (-distance).max(1e-4);
+        }
+    }
+}
+
+impl PanOrbitConstraints {
+    /// Whether the pitch limits stay within ±π/2, so the camera can never end up upside down
+    pub fn keeps_upright(&self) -> bool {
+        match (self.min_pitch, self.max_pitch) {
+            (Some(min_pitch), Some(max_pitch)) => {
+                min_pitch >= -std::f32::consts::FRAC_PI_2
+                    && max_pitch <= std::f32::consts::FRAC_PI_2
+            }
+            _ => false,
+        }
+    }
+
+    /// Limit the focus and pitch and keep out of [`PanOrbitConstraints::keep_out`],
+    /// the radius is limited separately with [`PanOrbitSettings::clamp_radius`](crate::pan_orbit_camera::PanOrbitSettings::clamp_radius)
+    pub fn apply(&self, view: &mut OrbitView) {
+        if let Some(bounds) = &self.focus_bounds {
+            view.focus = bounds.clamp(view.focus);
+        }
+        if let Some(min_pitch) = self.min_pitch {
+            view.pitch = view.pitch.max(min_pitch);
+        }
+        if let Some(max_pitch) = self.max_pitch {
+            view.pitch = view.pitch.min(max_pitch);
+        }
+        if let Some(keep_out) = &self.keep_out {
+            keep_out.push_out(view);
+        }
+    }
+}
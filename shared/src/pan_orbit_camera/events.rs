@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+
+use crate::pan_orbit_camera::constraints::PanOrbitConstraints;
+use crate::pan_orbit_camera::{
+    PanOrbitCamera, PanOrbitSettings, PanOrbitState, PanOrbitTransition,
+};
+
+/// Move every enabled [`PanOrbitCamera`] from code.
+/// These set the camera's target, so it eases there with [`PanOrbitSettings::smoothing`] like any other movement.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PanOrbitEvent {
+    /// Swing around the focus to this yaw and pitch in radians
+    OrbitTo { yaw: f32, pitch: f32 },
+    /// Orbit around a new point without changing the angle or distance
+    SetFocus(Vec3),
+    /// Move to this distance from the focus, within the camera's radius limits, see [`PanOrbitSettings::clamp_radius`]
+    ZoomTo(f32),
+    /// Orbit around wherever this entity is
+    LookAt(Entity),
+}
+
+pub fn pan_orbit_events(
+    mut commands: Commands,
+    settings: Res<PanOrbitSettings>,
+    mut events: EventReader<PanOrbitEvent>,
+    targets: Query<&GlobalTransform>,
+    mut query: Query<(
+        Entity,
+        &mut PanOrbitCamera,
+        &mut PanOrbitState,
+        Option<&PanOrbitConstraints>,
+    )>,
+) {
+    for event in events.iter() {
+        let focus = match *event {
+            PanOrbitEvent::SetFocus(focus) => Some(focus),
+            PanOrbitEvent::LookAt(target) => match targets.get(target) {
+                Ok(transform) => Some(transform.translation),
+                Err(e) => {
+                    warn!("Can't look at {:?}: {}", target, e);
+                    continue;
+                }
+            },
+            _ => None,
+        };
+
+        for (entity, mut pan_orbit, mut state, constraints) in query.iter_mut() {
+            if !pan_orbit.enabled {
+                continue;
+            }
+
+            match *event {
+                PanOrbitEvent::OrbitTo { yaw, pitch } => {
+                    pan_orbit.yaw = yaw;
+                    pan_orbit.pitch = pitch;
+                    pan_orbit.update_upside_down(constraints);
+                }
+                PanOrbitEvent::ZoomTo(radius) => {
+                    pan_orbit.radius = settings.clamp_radius(radius, constraints);
+                }
+                PanOrbitEvent::SetFocus(_) | PanOrbitEvent::LookAt(_) => {
+                    if let Some(focus) = focus {
+                        pan_orbit.focus = focus;
+                    }
+                }
+            }
+
+            // the event wins over anything that was already moving the camera
+            state.velocity = Default::default();
+            commands.entity(entity).remove::<PanOrbitTransition>();
+        }
+    }
+}
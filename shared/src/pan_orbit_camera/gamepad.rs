@@ -0,0 +1,219 @@
+use bevy::prelude::*;
+
+use crate::pan_orbit_camera::{focused_window, PanOrbitInput, PanOrbitSettings};
+use crate::sim_clock::{frame_seconds, SimClock};
+
+/// Gamepad bindings and speeds for the pan/orbit camera.
+/// Gamepads are only read natively with the `gamepad` feature, since gilrs doesn't work in Firefox.
+#[derive(Clone, Debug)]
+pub struct PanOrbitGamepad {
+    pub orbit_stick: Stick,
+    pub pan_stick: Stick,
+    pub zoom_in: GamepadButtonType,
+    pub zoom_out: GamepadButtonType,
+    pub stick_response: AxisResponse,
+    pub trigger_response: AxisResponse,
+
+    /// Radians per second with the stick pushed all the way
+    pub orbit_speed: f32,
+    /// Fractions of the view per second with the stick pushed all the way
+    pub pan_speed: f32,
+    /// How quickly the radius shrinks or grows with the trigger pulled all the way, as a rate per second
+    pub zoom_speed: f32,
+}
+
+impl Default for PanOrbitGamepad {
+    fn default() -> Self {
+        PanOrbitGamepad {
+            orbit_stick: Stick::Left,
+            pan_stick: Stick::Right,
+            zoom_in: GamepadButtonType::RightTrigger2,
+            zoom_out: GamepadButtonType::LeftTrigger2,
+            stick_response: AxisResponse {
+                dead_zone: 0.15,
+                exponent: 2.0,
+            },
+            trigger_response: AxisResponse {
+                dead_zone: 0.05,
+                exponent: 1.0,
+            },
+            orbit_speed: std::f32::consts::PI,
+            pan_speed: 0.5,
+            zoom_speed: 1.5,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stick {
+    Left,
+    Right,
+}
+
+impl Stick {
+    fn axes(&self) -> (GamepadAxisType, GamepadAxisType) {
+        match self {
+            Stick::Left => (GamepadAxisType::LeftStickX, GamepadAxisType::LeftStickY),
+            Stick::Right => (GamepadAxisType::RightStickX, GamepadAxisType::RightStickY),
+        }
+    }
+}
+
+/// Shapes how far an axis has to move before it does anything and how it ramps up after that
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AxisResponse {
+    /// Anything below this is treated as 0, so a worn stick doesn't drift the camera
+    pub dead_zone: f32,
+    /// 1 is linear, higher gives finer control near the center and the same top speed
+    pub exponent: f32,
+}
+
+impl AxisResponse {
+    /// Map an axis value in `-1..=1` through the dead zone and curve
+    pub fn apply(&self, value: f32) -> f32 {
+        let magnitude = value.abs();
+        if magnitude <= self.dead_zone || self.dead_zone >= 1.0 {
+            return 0.0;
+        }
+
+        // rescale so the output starts from 0 at the edge of the dead zone instead of jumping
+        let scaled = ((magnitude - self.dead_zone) / (1.0 - self.dead_zone)).min(1.0);
+        scaled.powf(self.exponent).copysign(value)
+    }
+
+    /// Apply the dead zone and curve to the stick's distance from the center so diagonals aren't cut off
+    pub fn apply_stick(&self, stick: Vec2) -> Vec2 {
+        let magnitude = stick.length();
+        if magnitude == 0.0 {
+            return Vec2::ZERO;
+        }
+        stick / magnitude * self.apply(magnitude)
+    }
+}
+
+/// Orbit with the left stick, pan with the right stick and zoom with the triggers.
+/// The bindings can be changed with [`PanOrbitSettings::gamepad`].
+#[allow(clippy::too_many_arguments)]
+pub fn gamepad_input(
+    time: Res<Time>,
+    clock: Option<Res<SimClock>>,
+    windows: Res<Windows>,
+    settings: Res<PanOrbitSettings>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    buttons: Res<Axis<GamepadButton>>,
+    mut orbiting: Local<bool>,
+    mut input: ResMut<PanOrbitInput>,
+) {
+    let bindings = match &settings.gamepad {
+        Some(bindings) => bindings,
+        None => return,
+    };
+    let delta_seconds = frame_seconds(&time, clock.as_deref());
+
+    let mut orbit = Vec2::ZERO;
+    let mut pan = Vec2::ZERO;
+    let mut zoom = 0.0;
+    for &gamepad in gamepads.iter() {
+        let stick = |stick: Stick| {
+            let (x, y) = stick.axes();
+            let x = axes.get(GamepadAxis(gamepad, x)).unwrap_or(0.0);
+            let y = axes.get(GamepadAxis(gamepad, y)).unwrap_or(0.0);
+            // sticks point y up but the camera input is in screen space like the mouse
+            bindings.stick_response.apply_stick(Vec2::new(x, -y))
+        };
+        let trigger = |button: GamepadButtonType| {
+            let value = buttons.get(GamepadButton(gamepad, button)).unwrap_or(0.0);
+            bindings.trigger_response.apply(value)
+        };
+
+        orbit += stick(bindings.orbit_stick);
+        pan += stick(bindings.pan_stick);
+        zoom += trigger(bindings.zoom_in) - trigger(bindings.zoom_out);
+    }
+
+    if orbit == Vec2::ZERO && pan == Vec2::ZERO && zoom == 0.0 && !*orbiting {
+        return;
+    }
+    match focused_window(&windows) {
+        Some((window_id, _)) => input.window = Some(window_id),
+        None => return,
+    }
+
+    if (orbit != Vec2::ZERO) != *orbiting {
+        *orbiting = orbit != Vec2::ZERO;
+        input.orbit_changed = true;
+    }
+
+    input.orbit += orbit
+        * settings.invert()
+        * bindings.orbit_speed
+        * settings.orbit_sensitivity
+        * delta_seconds;
+    input.pan +=
+        pan * settings.invert() * bindings.pan_speed * settings.pan_sensitivity * delta_seconds;
+    if zoom != 0.0 {
+        input.zoom += 1.0 - (-zoom * bindings.zoom_speed * delta_seconds).exp();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STICK: AxisResponse = AxisResponse {
+        dead_zone: 0.2,
+        exponent: 2.0,
+    };
+
+    #[test]
+    fn the_dead_zone_is_zero() {
+        for value in [0.0, 0.1, -0.1, 0.2, -0.2] {
+            assert_eq!(STICK.apply(value), 0.0);
+        }
+    }
+
+    #[test]
+    fn the_edge_of_the_dead_zone_starts_from_zero() {
+        assert!(STICK.apply(0.2001).abs() < 1e-6);
+        assert!(STICK.apply(-0.2001).abs() < 1e-6);
+    }
+
+    #[test]
+    fn full_tilt_is_full_speed() {
+        assert_eq!(STICK.apply(1.0), 1.0);
+        assert_eq!(STICK.apply(-1.0), -1.0);
+        // some sticks overshoot a little
+        assert_eq!(STICK.apply(1.1), 1.0);
+    }
+
+    #[test]
+    fn the_curve_is_applied_after_the_dead_zone() {
+        // halfway between the dead zone and full tilt, squared
+        assert!((STICK.apply(0.6) - 0.25).abs() < 1e-6);
+        assert!((STICK.apply(-0.6) + 0.25).abs() < 1e-6);
+
+        let linear = AxisResponse {
+            dead_zone: 0.0,
+            exponent: 1.0,
+        };
+        assert_eq!(linear.apply(0.3), 0.3);
+    }
+
+    #[test]
+    fn a_dead_zone_of_one_ignores_everything() {
+        let broken = AxisResponse {
+            dead_zone: 1.0,
+            exponent: 1.0,
+        };
+        assert_eq!(broken.apply(1.0), 0.0);
+    }
+
+    #[test]
+    fn sticks_keep_their_direction() {
+        let response = STICK.apply_stick(Vec2::new(0.6, 0.8));
+        assert!(response.abs_diff_eq(Vec2::new(0.6, 0.8), 1e-6));
+        assert_eq!(STICK.apply_stick(Vec2::new(0.1, 0.1)), Vec2::ZERO);
+        assert_eq!(STICK.apply_stick(Vec2::ZERO), Vec2::ZERO);
+    }
+}
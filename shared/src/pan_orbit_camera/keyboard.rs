@@ -0,0 +1,172 @@
+use bevy::prelude::*;
+
+use crate::pan_orbit_camera::{focused_window, PanOrbitInput, PanOrbitSettings};
+use crate::sim_clock::{frame_seconds, SimClock};
+
+/// Keyboard bindings and speeds for the pan/orbit camera.
+/// The direction keys act like dragging the scene with the mouse, orbiting by default and panning while a pan modifier is held.
+#[derive(Clone, Debug)]
+pub struct PanOrbitKeys {
+    pub left: Vec<KeyCode>,
+    pub right: Vec<KeyCode>,
+    pub up: Vec<KeyCode>,
+    pub down: Vec<KeyCode>,
+    pub zoom_in: Vec<KeyCode>,
+    pub zoom_out: Vec<KeyCode>,
+    /// Holding any of these switches the direction keys from orbiting to panning
+    pub pan_modifiers: Vec<KeyCode>,
+
+    /// Radians per second
+    pub orbit_speed: f32,
+    /// Fractions of the view per second
+    pub pan_speed: f32,
+    /// How quickly the radius shrinks or grows, as a rate per second
+    pub zoom_speed: f32,
+}
+
+impl Default for PanOrbitKeys {
+    fn default() -> Self {
+        PanOrbitKeys {
+            left: vec![KeyCode::Left, KeyCode::A],
+            right: vec![KeyCode::Right, KeyCode::D],
+            up: vec![KeyCode::Up, KeyCode::W],
+            down: vec![KeyCode::Down, KeyCode::S],
+            zoom_in: vec![KeyCode::E, KeyCode::PageUp],
+            zoom_out: vec![KeyCode::Q, KeyCode::PageDown],
+            pan_modifiers: vec![KeyCode::LShift, KeyCode::RShift],
+            orbit_speed: std::f32::consts::FRAC_PI_2,
+            pan_speed: 0.5,
+            zoom_speed: 1.0,
+        }
+    }
+}
+
+impl PanOrbitKeys {
+    /// The direction the keys are pushing in screen space, y is down to match the mouse
+    fn direction(&self, input_keyboard: &Input<KeyCode>) -> Vec2 {
+        let axis = |negative: &[KeyCode], positive: &[KeyCode]| {
+            let negative = any_pressed(input_keyboard, negative);
+            let positive = any_pressed(input_keyboard, positive);
+            positive as i8 as f32 - negative as i8 as f32
+        };
+
+        Vec2::new(axis(&self.left, &self.right), axis(&self.up, &self.down))
+    }
+
+    fn zoom_direction(&self, input_keyboard: &Input<KeyCode>) -> f32 {
+        let zoom_in = any_pressed(input_keyboard, &self.zoom_in);
+        let zoom_out = any_pressed(input_keyboard, &self.zoom_out);
+        zoom_in as i8 as f32 - zoom_out as i8 as f32
+    }
+
+    /// Whether the direction keys pan instead of orbiting
+    fn pans(&self, input_keyboard: &Input<KeyCode>) -> bool {
+        any_pressed(input_keyboard, &self.pan_modifiers)
+    }
+
+    fn any_direction_changed(&self, input_keyboard: &Input<KeyCode>) -> bool {
+        self.left
+            .iter()
+            .chain(&self.right)
+            .chain(&self.up)
+            .chain(&self.down)
+            .any(|&key| input_keyboard.just_pressed(key) || input_keyboard.just_released(key))
+    }
+}
+
+fn any_pressed(input_keyboard: &Input<KeyCode>, keys: &[KeyCode]) -> bool {
+    keys.iter().any(|&key| input_keyboard.pressed(key))
+}
+
+/// Orbit with the arrow keys or WASD, pan by holding shift and zoom with Q/E or PageUp/PageDown.
+/// The bindings can be changed with [`PanOrbitSettings::keyboard`].
+pub fn keyboard_input(
+    time: Res<Time>,
+    clock: Option<Res<SimClock>>,
+    windows: Res<Windows>,
+    settings: Res<PanOrbitSettings>,
+    input_keyboard: Res<Input<KeyCode>>,
+    mut input: ResMut<PanOrbitInput>,
+) {
+    let keys = match &settings.keyboard {
+        Some(keys) => keys,
+        None => return,
+    };
+    match focused_window(&windows) {
+        Some((window_id, _)) => input.window = Some(window_id),
+        None => return,
+    }
+    let delta_seconds = frame_seconds(&time, clock.as_deref());
+
+    let direction = keys.direction(&input_keyboard);
+    if direction != Vec2::ZERO {
+        let direction = direction.normalize() * settings.invert();
+        if keys.pans(&input_keyboard) {
+            input.pan += direction * keys.pan_speed * settings.pan_sensitivity * delta_seconds;
+        } else {
+            input.orbit +=
+                direction * keys.orbit_speed * settings.orbit_sensitivity * delta_seconds;
+        }
+    }
+
+    let zoom_direction = keys.zoom_direction(&input_keyboard);
+    if zoom_direction != 0.0 {
+        // exponential so that holding the key zooms at the same rate regardless of frame rate
+        input.zoom += 1.0 - (-zoom_direction * keys.zoom_speed * delta_seconds).exp();
+    }
+
+    if keys.any_direction_changed(&input_keyboard) {
+        input.orbit_changed = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pressed(keys: &[KeyCode]) -> Input<KeyCode> {
+        let mut input_keyboard = Input::default();
+        for &key in keys {
+            input_keyboard.press(key);
+        }
+        input_keyboard
+    }
+
+    #[test]
+    fn direction_keys_combine() {
+        let keys = PanOrbitKeys::default();
+        assert_eq!(keys.direction(&pressed(&[])), Vec2::ZERO);
+        assert_eq!(
+            keys.direction(&pressed(&[KeyCode::W])),
+            Vec2::new(0.0, -1.0)
+        );
+        assert_eq!(
+            keys.direction(&pressed(&[KeyCode::Right, KeyCode::S])),
+            Vec2::new(1.0, 1.0)
+        );
+        // opposite keys cancel out, even when they're different bindings
+        assert_eq!(
+            keys.direction(&pressed(&[KeyCode::A, KeyCode::Right])),
+            Vec2::ZERO
+        );
+    }
+
+    #[test]
+    fn zoom_keys_combine() {
+        let keys = PanOrbitKeys::default();
+        assert_eq!(keys.zoom_direction(&pressed(&[KeyCode::E])), 1.0);
+        assert_eq!(keys.zoom_direction(&pressed(&[KeyCode::PageDown])), -1.0);
+        assert_eq!(
+            keys.zoom_direction(&pressed(&[KeyCode::PageUp, KeyCode::Q])),
+            0.0
+        );
+    }
+
+    #[test]
+    fn shift_switches_to_panning() {
+        let keys = PanOrbitKeys::default();
+        assert!(!keys.pans(&pressed(&[KeyCode::W])));
+        assert!(keys.pans(&pressed(&[KeyCode::W, KeyCode::LShift])));
+        assert!(keys.pans(&pressed(&[KeyCode::RShift])));
+    }
+}
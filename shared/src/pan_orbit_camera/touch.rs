@@ -0,0 +1,208 @@
+use bevy::input::touch::Touches;
+use bevy::prelude::*;
+
+use crate::pan_orbit_camera::{focused_window, PanOrbitInput, PanOrbitSettings};
+
+/// Where a single finger was last frame and where it is now, in pixels
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TouchPoint {
+    pub previous: Vec2,
+    pub position: Vec2,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TouchGesture {
+    None,
+    /// One finger dragging, by this many pixels
+    Orbit(Vec2),
+    /// Two fingers dragging and pinching
+    PanZoom {
+        /// How far the midpoint between the fingers moved in pixels
+        pan: Vec2,
+        /// The fraction of the radius to zoom in by, spreading the fingers apart zooms in
+        zoom: f32,
+    },
+}
+
+/// Work out what the fingers currently on the screen are asking the camera to do.
+/// One finger orbits, two fingers pan and pinch to zoom, anything else is ignored.
+pub fn recognize_gesture(points: &[TouchPoint]) -> TouchGesture {
+    match points {
+        [finger] => TouchGesture::Orbit(finger.position - finger.previous),
+        [a, b] => {
+            let previous_midpoint = (a.previous + b.previous) / 2.0;
+            let midpoint = (a.position + b.position) / 2.0;
+
+            let previous_distance = a.previous.distance(b.previous);
+            let distance = a.position.distance(b.position);
+            // if the fingers are on top of each other we can't tell how far they've pinched
+            let zoom = if previous_distance > f32::EPSILON && distance > f32::EPSILON {
+                1.0 - previous_distance / distance
+            } else {
+                0.0
+            };
+
+            TouchGesture::PanZoom {
+                pan: midpoint - previous_midpoint,
+                zoom,
+            }
+        }
+        // 3+ fingers is probably an OS gesture or a resting palm
+        _ => TouchGesture::None,
+    }
+}
+
+/// Orbit with one finger, pan with two fingers and pinch to zoom
+pub fn touch_input(
+    windows: Res<Windows>,
+    touches: Res<Touches>,
+    settings: Res<PanOrbitSettings>,
+    mut input: ResMut<PanOrbitInput>,
+) {
+    let (window_id, window) = match focused_window(&windows) {
+        Some(focused) => focused,
+        None => return,
+    };
+    input.window = Some(window_id);
+
+    let points = touches
+        .iter()
+        .map(|touch| TouchPoint {
+            previous: touch.previous_position(),
+            position: touch.position(),
+        })
+        .collect::<Vec<_>>();
+
+    match recognize_gesture(&points) {
+        TouchGesture::None => {}
+        TouchGesture::Orbit(delta) => input.orbit += settings.orbit_delta(delta, window),
+        TouchGesture::PanZoom { pan, zoom } => {
+            input.pan += settings.pan_delta(pan, window);
+            input.zoom += zoom;
+        }
+    }
+
+    if !points.is_empty() {
+        input.held = true;
+    }
+
+    // the number of fingers changing means the gesture changed
+    if touches.iter_just_pressed().next().is_some() || touches.iter_just_released().next().is_some()
+    {
+        input.orbit_changed = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(previous: (f32, f32), position: (f32, f32)) -> TouchPoint {
+        TouchPoint {
+            previous: Vec2::new(previous.0, previous.1),
+            position: Vec2::new(position.0, position.1),
+        }
+    }
+
+    #[test]
+    fn no_fingers_do_nothing() {
+        assert_eq!(recognize_gesture(&[]), TouchGesture::None);
+    }
+
+    #[test]
+    fn one_finger_orbits() {
+        assert_eq!(
+            recognize_gesture(&[point((10.0, 10.0), (13.0, 6.0))]),
+            TouchGesture::Orbit(Vec2::new(3.0, -4.0))
+        );
+    }
+
+    #[test]
+    fn two_fingers_moving_together_pan() {
+        let gesture = recognize_gesture(&[
+            point((0.0, 0.0), (5.0, 2.0)),
+            point((100.0, 0.0), (105.0, 2.0)),
+        ]);
+        assert_eq!(
+            gesture,
+            TouchGesture::PanZoom {
+                pan: Vec2::new(5.0, 2.0),
+                zoom: 0.0,
+            }
+        );
+    }
+
+    #[test]
+    fn two_fingers_spreading_zoom_in() {
+        let gesture = recognize_gesture(&[
+            point((50.0, 0.0), (25.0, 0.0)),
+            point((150.0, 0.0), (175.0, 0.0)),
+        ]);
+        // from 100 to 150 pixels apart, around a midpoint that didn't move
+        assert_eq!(
+            gesture,
+            TouchGesture::PanZoom {
+                pan: Vec2::ZERO,
+                zoom: 1.0 - 100.0 / 150.0,
+            }
+        );
+    }
+
+    #[test]
+    fn two_fingers_pinching_zoom_out() {
+        match recognize_gesture(&[
+            point((0.0, 0.0), (25.0, 0.0)),
+            point((100.0, 0.0), (75.0, 0.0)),
+        ]) {
+            TouchGesture::PanZoom { pan, zoom } => {
+                assert_eq!(pan, Vec2::ZERO);
+                assert!(zoom < 0.0);
+            }
+            gesture => panic!("expected a pan and zoom, got {:?}", gesture),
+        }
+    }
+
+    #[test]
+    fn fingers_on_top_of_each_other_dont_zoom() {
+        let gesture = recognize_gesture(&[
+            point((10.0, 10.0), (20.0, 10.0)),
+            point((10.0, 10.0), (20.0, 10.0)),
+        ]);
+        assert_eq!(
+            gesture,
+            TouchGesture::PanZoom {
+                pan: Vec2::new(10.0, 0.0),
+                zoom: 0.0,
+            }
+        );
+    }
+
+    #[test]
+    fn lifting_a_finger_mid_gesture_orbits_without_jumping() {
+        let pinch = [
+            point((0.0, 0.0), (10.0, 0.0)),
+            point((100.0, 0.0), (90.0, 0.0)),
+        ];
+        assert!(matches!(
+            recognize_gesture(&pinch),
+            TouchGesture::PanZoom { .. }
+        ));
+
+        // the finger left behind only orbits by its own movement, not from the old midpoint
+        let lifted = [point((90.0, 0.0), (91.0, 0.0))];
+        assert_eq!(
+            recognize_gesture(&lifted),
+            TouchGesture::Orbit(Vec2::new(1.0, 0.0))
+        );
+    }
+
+    #[test]
+    fn a_third_finger_stops_the_gesture() {
+        let gesture = recognize_gesture(&[
+            point((0.0, 0.0), (10.0, 0.0)),
+            point((100.0, 0.0), (90.0, 0.0)),
+            point((50.0, 50.0), (50.0, 60.0)),
+        ]);
+        assert_eq!(gesture, TouchGesture::None);
+    }
+}
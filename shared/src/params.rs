@@ -0,0 +1,384 @@
+//! Live tweakable parameters.
+//! Projects keep their knobs in reflected resources and register them with [`RegisterParams::register_params`],
+//! then [`ParamsSettings::toggle_key`] shows a panel with a control for every field.
+//! Fields that aren't at their defaults are kept in the [`ViewLink`] so they're shared along with the view.
+
+use std::ops::RangeInclusive;
+
+use bevy::app::Events;
+use bevy::input::mouse::{MouseMotion, MouseWheel};
+use bevy::prelude::*;
+use bevy::reflect::{GetTypeRegistration, Struct};
+use bevy_egui::egui::emath::Numeric;
+use bevy_egui::{egui, EguiContext, EguiPlugin, EguiSystem};
+
+use crate::art_piece::ArtState;
+use crate::launch_config::{format_field, LaunchConfig};
+use crate::view_link::ViewLink;
+
+/// Adds the egui panel that edits every registered [`Params`] resource
+pub struct ParamsPlugin;
+
+impl Plugin for ParamsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(EguiPlugin)
+            .init_resource::<ParamsSettings>()
+            .init_resource::<ParamsRegistry>()
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                block_captured_input.after(EguiSystem::ProcessInput),
+            )
+            .add_system(toggle_params_panel)
+            .add_system(params_panel.exclusive_system().at_end());
+    }
+}
+
+pub struct ParamsSettings {
+    pub toggle_key: KeyCode,
+    pub visible: bool,
+}
+
+impl Default for ParamsSettings {
+    fn default() -> Self {
+        ParamsSettings {
+            toggle_key: KeyCode::F1,
+            visible: false,
+        }
+    }
+}
+
+/// A resource of parameters that can be edited from the panel.
+/// Fields that are numbers, `bool`s, [`Color`]s or [`Vec3`]s get a control, anything else is shown as read only.
+pub trait Params: Struct + GetTypeRegistration + Clone + Default + Send + Sync + 'static {
+    /// The heading of this resource's section in the panel
+    const NAME: &'static str;
+
+    /// The slider range for a number field, fields without one get a drag value instead.
+    /// Ranges over more than two orders of magnitude above 0 slide logarithmically.
+    fn range(_field: &str) -> Option<RangeInclusive<f64>> {
+        None
+    }
+}
+
+pub trait RegisterParams {
+    /// Add `T` as a resource and a section of the panel, with any of its fields set in the [`LaunchConfig`]
+    fn register_params<T: Params>(&mut self) -> &mut Self;
+}
+
+impl RegisterParams for App {
+    fn register_params<T: Params>(&mut self) -> &mut Self {
+        self.register_type::<T>()
+            .add_system(update_link_params::<T>);
+        insert_params::<T>(&mut self.world);
+
+        self.world
+            .get_resource_or_insert_with(ParamsRegistry::default)
+            .sections
+            .push(ParamsSection {
+                name: T::NAME,
+                ui: params_ui::<T>,
+                insert: insert_params::<T>,
+                remove: remove_params::<T>,
+                piece: None,
+            });
+        self
+    }
+}
+
+#[derive(Default)]
+pub(crate) struct ParamsRegistry {
+    sections: Vec<ParamsSection>,
+}
+
+impl ParamsRegistry {
+    pub(crate) fn len(&self) -> usize {
+        self.sections.len()
+    }
+
+    /// Only show the sections registered after the first `from` while `piece` is showing
+    pub(crate) fn set_piece(&mut self, from: usize, piece: ArtState) {
+        for section in self.sections.iter_mut().skip(from) {
+            section.piece = Some(piece);
+        }
+    }
+
+    fn piece_sections(&self, piece: ArtState) -> impl Iterator<Item = &ParamsSection> {
+        self.sections
+            .iter()
+            .filter(move |section| section.piece == Some(piece))
+    }
+
+    /// The sections for what's showing, which is every section in an app without an [`ArtState`]
+    fn showing(&self, world: &World) -> impl Iterator<Item = &ParamsSection> {
+        let current = world
+            .get_resource::<State<ArtState>>()
+            .map(|state| *state.current());
+        self.sections
+            .iter()
+            .filter(move |section| section.piece.is_none() || section.piece == current)
+    }
+}
+
+struct ParamsSection {
+    name: &'static str,
+    ui: fn(&mut World, &mut egui::Ui),
+    insert: fn(&mut World),
+    remove: fn(&mut World),
+    /// The piece that registered these, they're hidden while it isn't showing
+    piece: Option<ArtState>,
+}
+
+/// Add `piece`'s parameters, starting from their defaults and whatever the [`LaunchConfig`] sets
+pub(crate) fn insert_piece_params(world: &mut World, piece: ArtState) {
+    let registry = world.get_resource::<ParamsRegistry>().unwrap();
+    let inserts = registry
+        .piece_sections(piece)
+        .map(|section| section.insert)
+        .collect::<Vec<_>>();
+    for insert in inserts {
+        insert(world);
+    }
+}
+
+/// Remove `piece`'s parameters while it isn't showing
+pub(crate) fn remove_piece_params(world: &mut World, piece: ArtState) {
+    let registry = world.get_resource::<ParamsRegistry>().unwrap();
+    let removes = registry
+        .piece_sections(piece)
+        .map(|section| section.remove)
+        .collect::<Vec<_>>();
+    for remove in removes {
+        remove(world);
+    }
+}
+
+fn insert_params<T: Params>(world: &mut World) {
+    let mut params = T::default();
+    if let Some(mut config) = world.get_resource_mut::<LaunchConfig>() {
+        config.apply(&mut params);
+    }
+    world.insert_resource(params);
+}
+
+fn remove_params<T: Params>(world: &mut World) {
+    world.remove_resource::<T>();
+}
+
+fn toggle_params_panel(input_keyboard: Res<Input<KeyCode>>, mut settings: ResMut<ParamsSettings>) {
+    if input_keyboard.just_pressed(settings.toggle_key) {
+        settings.visible = !settings.visible;
+    }
+}
+
+/// Keep the camera still while the panel is being used
+fn block_captured_input(
+    mut egui_context: ResMut<EguiContext>,
+    mut ev_motion: ResMut<Events<MouseMotion>>,
+    mut ev_scroll: ResMut<Events<MouseWheel>>,
+    mut input_mouse: ResMut<Input<MouseButton>>,
+    mut input_keyboard: ResMut<Input<KeyCode>>,
+) {
+    let ctx = egui_context.ctx_mut();
+    if ctx.wants_pointer_input() {
+        ev_motion.clear();
+        ev_scroll.clear();
+        let pressed = input_mouse.get_pressed().copied().collect::<Vec<_>>();
+        for button in pressed {
+            input_mouse.reset(button);
+        }
+    }
+    if ctx.wants_keyboard_input() {
+        // keys that were already held before typing started keep working
+        let pressed = input_keyboard
+            .get_just_pressed()
+            .copied()
+            .collect::<Vec<_>>();
+        for key in pressed {
+            input_keyboard.reset(key);
+        }
+    }
+}
+
+fn params_panel(world: &mut World) {
+    let visible = world.get_resource::<ParamsSettings>().unwrap().visible;
+    if !visible {
+        return;
+    }
+
+    let ctx = world
+        .get_resource_mut::<EguiContext>()
+        .unwrap()
+        .ctx_mut()
+        .clone();
+    world.resource_scope(|world, registry: Mut<ParamsRegistry>| {
+        egui::Window::new("Parameters").show(&ctx, |ui| {
+            let sections = registry.showing(world).collect::<Vec<_>>();
+            if sections.is_empty() {
+                ui.label("Nothing to tweak");
+            }
+            for section in sections {
+                egui::CollapsingHeader::new(section.name)
+                    .default_open(true)
+                    .show(ui, |ui| (section.ui)(world, ui));
+            }
+        });
+    });
+}
+
+/// Put the fields of `T` that were changed from their defaults in the [`ViewLink`], and take them out when it's removed
+fn update_link_params<T: Params>(
+    params: Option<Res<T>>,
+    link: Option<ResMut<ViewLink>>,
+    mut linked: Local<bool>,
+) {
+    let mut link = match link {
+        Some(link) => link,
+        None => return,
+    };
+    if params
+        .as_ref()
+        .map_or(!*linked, |params| !params.is_changed())
+    {
+        return;
+    }
+
+    let mut values = link.params.clone();
+    let defaults = T::default();
+    for i in 0..defaults.field_len() {
+        let name = defaults.name_at(i).unwrap();
+        let value = params.as_ref().and_then(|params| {
+            let field = params.field(name).unwrap();
+            let is_default = field
+                .reflect_partial_eq(defaults.field_at(i).unwrap())
+                .unwrap_or(false);
+            if is_default {
+                None
+            } else {
+                format_field(field)
+            }
+        });
+        match value {
+            Some(value) => values.insert(name.to_string(), value),
+            None => values.remove(name),
+        };
+    }
+    *linked = params.is_some();
+
+    if link.params != values {
+        link.params = values;
+    }
+}
+
+/// Controls for every field of `T`, only touching the resource when something was changed
+fn params_ui<T: Params>(world: &mut World, ui: &mut egui::Ui) {
+    let mut params = match world.get_resource_mut::<T>() {
+        Some(params) => params,
+        None => return,
+    };
+    let mut edited = params.clone();
+    let defaults = T::default();
+    let mut changed = false;
+
+    egui::Grid::new(T::NAME)
+        .num_columns(3)
+        .striped(true)
+        .show(ui, |ui| {
+            for i in 0..edited.field_len() {
+                let name = edited.name_at(i).unwrap().to_string();
+                let field = edited.field_at_mut(i).unwrap();
+                ui.label(name.replace('_', " "));
+                changed |= field_ui(ui, field, T::range(&name));
+
+                let default = defaults.field(&name).unwrap();
+                let is_default = field.reflect_partial_eq(default).unwrap_or(false);
+                if ui
+                    .add_enabled(!is_default, egui::Button::new("↺").small())
+                    .on_hover_text("Reset to default")
+                    .clicked()
+                {
+                    field.apply(default);
+                    changed = true;
+                }
+                ui.end_row();
+            }
+        });
+    if ui.button("Reset all").clicked() {
+        edited = defaults;
+        changed = true;
+    }
+
+    if changed {
+        *params = edited;
+    }
+}
+
+/// Returns whether the value was changed
+fn field_ui(
+    ui: &mut egui::Ui,
+    value: &mut dyn Reflect,
+    range: Option<RangeInclusive<f64>>,
+) -> bool {
+    if value.is::<f32>() {
+        number_ui(ui, value.downcast_mut::<f32>().unwrap(), range)
+    } else if value.is::<f64>() {
+        number_ui(ui, value.downcast_mut::<f64>().unwrap(), range)
+    } else if value.is::<u32>() {
+        number_ui(ui, value.downcast_mut::<u32>().unwrap(), range)
+    } else if value.is::<i32>() {
+        number_ui(ui, value.downcast_mut::<i32>().unwrap(), range)
+    } else if value.is::<usize>() {
+        number_ui(ui, value.downcast_mut::<usize>().unwrap(), range)
+    } else if let Some(value) = value.downcast_mut::<bool>() {
+        ui.checkbox(value, "").changed()
+    } else if let Some(color) = value.downcast_mut::<Color>() {
+        let mut srgba = color
+            .as_rgba_f32()
+            .map(|channel| (channel * 255.0).round() as u8);
+        let changed = ui
+            .color_edit_button_srgba_unmultiplied(&mut srgba)
+            .changed();
+        if changed {
+            let [r, g, b, a] = srgba.map(|channel| channel as f32 / 255.0);
+            *color = Color::rgba(r, g, b, a);
+        }
+        changed
+    } else if let Some(vector) = value.downcast_mut::<Vec3>() {
+        let mut axes = vector.to_array();
+        let changed = ui
+            .horizontal(|ui| {
+                let mut changed = false;
+                for axis in axes.iter_mut() {
+                    changed |= ui.add(egui::DragValue::new(axis).speed(0.01)).changed();
+                }
+                changed
+            })
+            .inner;
+        if changed {
+            *vector = Vec3::from(axes);
+        }
+        changed
+    } else {
+        ui.label(value.type_name());
+        false
+    }
+}
+
+fn number_ui<N: Numeric>(
+    ui: &mut egui::Ui,
+    value: &mut N,
+    range: Option<RangeInclusive<f64>>,
+) -> bool {
+    match range {
+        Some(range) => {
+            let logarithmic = *range.start() > 0.0 && *range.end() / *range.start() > 100.0;
+            let range = N::from_f64(*range.start())..=N::from_f64(*range.end());
+            ui.add(egui::Slider::new(value, range).logarithmic(logarithmic))
+                .changed()
+        }
+        None => {
+            // drag in steps of about a hundredth of the current value
+            let speed = (value.to_f64().abs() * 0.01).max(if N::INTEGRAL { 1.0 } else { 0.001 });
+            ui.add(egui::DragValue::new(value).speed(speed)).changed()
+        }
+    }
+}
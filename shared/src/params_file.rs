@@ -0,0 +1,116 @@
+//! Load [`Params`] from RON files in `assets`, reloading them whenever they're saved.
+//! Invalid files are logged and the current values are kept, and the [`LaunchConfig`] still overrides them.
+
+use std::marker::PhantomData;
+
+use bevy::asset::{AssetLoader, LoadContext, LoadedAsset};
+use bevy::prelude::*;
+use bevy::reflect::TypeUuid;
+use bevy::utils::BoxedFuture;
+use serde::de::DeserializeOwned;
+
+use crate::launch_config::LaunchConfig;
+use crate::params::Params;
+
+/// Adds the [`ParamsFile`] asset, which [`LoadParamsFile::load_params_file`] needs
+pub struct ParamsFilePlugin;
+
+impl Plugin for ParamsFilePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<ParamsFile>()
+            .init_asset_loader::<ParamsFileLoader>();
+    }
+}
+
+/// The text of a `.params.ron` file, it's parsed into the [`Params`] type that asked for it
+#[derive(Debug, TypeUuid)]
+#[uuid = "5b3c3f4e-2f0c-4a4f-9d2a-7c1f6b8e4a11"]
+pub struct ParamsFile {
+    pub contents: String,
+}
+
+#[derive(Default)]
+struct ParamsFileLoader;
+
+impl AssetLoader for ParamsFileLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, anyhow::Result<()>> {
+        Box::pin(async move {
+            let contents = String::from_utf8(bytes.to_vec())?;
+            load_context.set_default_asset(LoadedAsset::new(ParamsFile { contents }));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["params.ron"]
+    }
+}
+
+pub trait LoadParamsFile {
+    /// Load `T` from `path` in `assets`, ending in `.params.ron`, now and every time the file changes.
+    /// Fields missing from the file get their default values.
+    fn load_params_file<T: Params + DeserializeOwned>(&mut self, path: &'static str) -> &mut Self;
+}
+
+impl LoadParamsFile for App {
+    fn load_params_file<T: Params + DeserializeOwned>(&mut self, path: &'static str) -> &mut Self {
+        let handle = self.world.get_resource::<AssetServer>().unwrap().load(path);
+        self.insert_resource(ParamsFileHandle::<T> {
+            path,
+            handle,
+            marker: PhantomData,
+        })
+        .add_system(reload_params::<T>)
+    }
+}
+
+struct ParamsFileHandle<T> {
+    path: &'static str,
+    handle: Handle<ParamsFile>,
+    marker: PhantomData<fn() -> T>,
+}
+
+fn reload_params<T: Params + DeserializeOwned>(
+    file: Res<ParamsFileHandle<T>>,
+    files: Res<Assets<ParamsFile>>,
+    mut events: EventReader<AssetEvent<ParamsFile>>,
+    mut config: Option<ResMut<LaunchConfig>>,
+    params: Option<ResMut<T>>,
+) {
+    let mut changed = false;
+    for event in events.iter() {
+        if let AssetEvent::Created { handle } | AssetEvent::Modified { handle } = event {
+            changed |= *handle == file.handle;
+        }
+    }
+    // pieces' params are put back at their defaults every time the piece is entered
+    let mut params = match params {
+        Some(params) if changed || params.is_added() => params,
+        _ => return,
+    };
+
+    let contents = match files.get(&file.handle) {
+        Some(loaded) => &loaded.contents,
+        None => return,
+    };
+    match ron::from_str::<T>(contents) {
+        Ok(mut loaded) => {
+            info!("Loaded {} from {}", T::NAME, file.path);
+            // whatever the app was launched with wins over the file
+            if let Some(config) = config.as_mut() {
+                config.apply(&mut loaded);
+            }
+            *params = loaded;
+        }
+        Err(e) => error!(
+            "Failed to load {} from {}, keeping the current values: {}",
+            T::NAME,
+            file.path,
+            e
+        ),
+    }
+}
@@ -0,0 +1,537 @@
+//! Save what the camera sees to a PNG.
+//! The main 3d view is rendered into an offscreen texture for the frame of the screenshot,
+//! copied into a buffer once the main pass is done and then encoded off the main thread.
+//! The window misses that one frame.
+
+use std::num::NonZeroU32;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use bevy::core_pipeline::node::MAIN_PASS_DRIVER;
+use bevy::prelude::*;
+use bevy::render::camera::{CameraPlugin, ExtractedCamera};
+use bevy::render::render_graph::{Node, NodeRunError, RenderGraph, RenderGraphContext};
+use bevy::render::render_resource::{
+    Buffer, BufferDescriptor, BufferUsages, Extent3d, ImageCopyBuffer, ImageCopyTexture,
+    ImageDataLayout, MapMode, Origin3d, Texture, TextureAspect, TextureDescriptor,
+    TextureDimension, TextureFormat, TextureUsages, TextureViewDescriptor,
+};
+use bevy::render::renderer::{RenderContext, RenderDevice};
+use bevy::render::texture::BevyDefault;
+use bevy::render::view::{ExtractedView, Msaa, ViewDepthTexture, ViewTarget};
+use bevy::render::{RenderApp, RenderStage, RenderWorld};
+use bevy::tasks::IoTaskPool;
+use bevy::window::WindowId;
+
+use crate::pan_orbit_camera::focused_window;
+
+const SCREENSHOT_NODE: &str = "screenshot";
+
+/// Press [`ScreenshotSettings::key`] to save a PNG of the focused window.
+/// Natively it's written next to wherever the project was run from as `{file_prefix}_{timestamp}.png`, on wasm it's downloaded.
+pub struct ScreenshotPlugin {
+    pub file_prefix: String,
+}
+
+impl Plugin for ScreenshotPlugin {
+    fn build(&self, app: &mut App) {
+        let captured = CapturedScreenshots::default();
+
+        app.init_resource::<ScreenshotSettings>()
+            .insert_resource(Screenshots {
+                file_prefix: self.file_prefix.clone(),
+                requests: Vec::new(),
+                in_flight: captured.in_flight.clone(),
+            })
+            .insert_resource(captured.clone())
+            .add_system(screenshot_input)
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                save_screenshots.label(ScreenshotSystem::Save),
+            );
+
+        let render_app = app.sub_app_mut(RenderApp);
+        render_app
+            .insert_resource(captured)
+            .init_resource::<ScreenshotRequests>()
+            .init_resource::<ScreenshotCaptures>()
+            .add_system_to_stage(RenderStage::Extract, extract_screenshot_requests)
+            .add_system_to_stage(RenderStage::Queue, queue_screenshots)
+            .add_system_to_stage(RenderStage::Cleanup, finish_screenshot_captures);
+
+        let mut graph = render_app.world.get_resource_mut::<RenderGraph>().unwrap();
+        graph.add_node(SCREENSHOT_NODE, ScreenshotNode);
+        graph
+            .add_node_edge(MAIN_PASS_DRIVER, SCREENSHOT_NODE)
+            .unwrap();
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, SystemLabel)]
+pub enum ScreenshotSystem {
+    /// Starts saving the frames captured last frame
+    Save,
+}
+
+pub struct ScreenshotSettings {
+    pub key: KeyCode,
+    /// Render at this many times the window's resolution and average it back down, 1 turns it off
+    pub supersampling: u32,
+}
+
+impl Default for ScreenshotSettings {
+    fn default() -> Self {
+        ScreenshotSettings {
+            key: KeyCode::F12,
+            supersampling: 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ScreenshotRequest {
+    pub window: WindowId,
+    pub file_name: String,
+    pub supersampling: u32,
+    /// Render at this width and height instead of the window's, the camera's projection has to be set up for it
+    pub size: Option<(u32, u32)>,
+}
+
+/// Screenshots waiting for the next frame to be rendered
+pub struct Screenshots {
+    file_prefix: String,
+    requests: Vec<ScreenshotRequest>,
+    in_flight: Arc<AtomicUsize>,
+}
+
+impl Screenshots {
+    /// Save the next frame of `window` with a timestamped name
+    pub fn take(&mut self, window: WindowId, supersampling: u32) {
+        let file_name = format!("{}_{}.png", self.file_prefix, timestamp());
+        self.take_as(window, file_name, supersampling);
+    }
+
+    pub fn take_as(&mut self, window: WindowId, file_name: impl Into<String>, supersampling: u32) {
+        self.request(ScreenshotRequest {
+            window,
+            file_name: file_name.into(),
+            supersampling,
+            size: None,
+        });
+    }
+
+    pub fn request(&mut self, request: ScreenshotRequest) {
+        self.in_flight.fetch_add(1, Ordering::SeqCst);
+        self.requests.push(ScreenshotRequest {
+            supersampling: request.supersampling.max(1),
+            ..request
+        });
+    }
+
+    /// How many screenshots have been asked for but aren't saved yet
+    pub fn in_flight(&self) -> usize {
+        self.in_flight.load(Ordering::SeqCst)
+    }
+}
+
+fn screenshot_input(
+    windows: Res<Windows>,
+    settings: Res<ScreenshotSettings>,
+    input_keyboard: Res<Input<KeyCode>>,
+    mut screenshots: ResMut<Screenshots>,
+) {
+    if input_keyboard.just_pressed(settings.key) {
+        let window = focused_window(&windows).map_or_else(WindowId::primary, |(id, _)| id);
+        screenshots.take(window, settings.supersampling);
+    }
+}
+
+/// Requests extracted into the render world
+#[derive(Default)]
+struct ScreenshotRequests(Vec<ScreenshotRequest>);
+
+/// A frame being copied out of the GPU
+struct ScreenshotCapture {
+    request: ScreenshotRequest,
+    texture: Texture,
+    buffer: Buffer,
+    width: u32,
+    height: u32,
+    /// Rows in the buffer are padded out to wgpu's copy alignment
+    padded_bytes_per_row: u32,
+    format: TextureFormat,
+}
+
+/// Render world captures that are waiting for the main pass to finish
+#[derive(Default)]
+struct ScreenshotCaptures(Vec<ScreenshotCapture>);
+
+/// Captures that have been submitted to the GPU, shared with the main world so it can save them
+#[derive(Clone, Default)]
+struct CapturedScreenshots {
+    finished: Arc<Mutex<Vec<ScreenshotCapture>>>,
+    /// Requests that haven't been saved yet, from when they're made until they're written or dropped
+    in_flight: Arc<AtomicUsize>,
+}
+
+fn extract_screenshot_requests(
+    mut screenshots: ResMut<Screenshots>,
+    mut render_world: ResMut<RenderWorld>,
+) {
+    if screenshots.requests.is_empty() {
+        return;
+    }
+    let mut requests = render_world
+        .get_resource_mut::<ScreenshotRequests>()
+        .unwrap();
+    requests.0.append(&mut screenshots.requests);
+}
+
+/// Swap the view's targets for textures we can copy from
+fn queue_screenshots(
+    mut requests: ResMut<ScreenshotRequests>,
+    mut captures: ResMut<ScreenshotCaptures>,
+    captured: Res<CapturedScreenshots>,
+    render_device: Res<RenderDevice>,
+    msaa: Res<Msaa>,
+    mut views: Query<(
+        &ExtractedCamera,
+        &ExtractedView,
+        &mut ViewTarget,
+        &mut ViewDepthTexture,
+    )>,
+) {
+    let max_size = render_device.limits().max_texture_dimension_2d;
+
+    for request in requests.0.drain(..) {
+        let view = views.iter_mut().find(|(camera, _, _, _)| {
+            camera.window_id == request.window
+                && camera.name.as_deref() == Some(CameraPlugin::CAMERA_3D)
+        });
+        let (_, view, mut target, mut depth) = match view {
+            Some(view) => view,
+            None => {
+                warn!("There's no 3d camera to take a screenshot of");
+                captured.in_flight.fetch_sub(1, Ordering::SeqCst);
+                continue;
+            }
+        };
+        let (width, height) = request.size.unwrap_or((view.width, view.height));
+        if width == 0 || height == 0 {
+            captured.in_flight.fetch_sub(1, Ordering::SeqCst);
+            continue;
+        }
+        if width.max(height) > max_size {
+            warn!(
+                "Can't take a {}x{} screenshot, the GPU only goes up to {}",
+                width, height, max_size
+            );
+            captured.in_flight.fetch_sub(1, Ordering::SeqCst);
+            continue;
+        }
+
+        // stay within what the GPU can render to
+        let supersampling = request
+            .supersampling
+            .min(max_size / width.max(height))
+            .max(1);
+        let size = Extent3d {
+            width: width * supersampling,
+            height: height * supersampling,
+            depth_or_array_layers: 1,
+        };
+        // the pipelines were specialized for this format, so we have to match it
+        let format = TextureFormat::bevy_default();
+        let descriptor = |label, sample_count, format, usage| TextureDescriptor {
+            label: Some(label),
+            size,
+            mip_level_count: 1,
+            sample_count,
+            dimension: TextureDimension::D2,
+            format,
+            usage,
+        };
+
+        let texture = render_device.create_texture(&descriptor(
+            "screenshot_texture",
+            1,
+            format,
+            TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+        ));
+        target.view = texture.create_view(&TextureViewDescriptor::default());
+        target.sampled_target = (msaa.samples > 1).then(|| {
+            render_device
+                .create_texture(&descriptor(
+                    "screenshot_sampled_texture",
+                    msaa.samples,
+                    format,
+                    TextureUsages::RENDER_ATTACHMENT,
+                ))
+                .create_view(&TextureViewDescriptor::default())
+        });
+        let depth_texture = render_device.create_texture(&descriptor(
+            "screenshot_depth_texture",
+            msaa.samples,
+            TextureFormat::Depth32Float,
+            TextureUsages::RENDER_ATTACHMENT,
+        ));
+        *depth = ViewDepthTexture {
+            view: depth_texture.create_view(&TextureViewDescriptor::default()),
+            texture: depth_texture,
+        };
+
+        let padded_bytes_per_row = padded_bytes_per_row(size.width);
+        let buffer = render_device.create_buffer(&BufferDescriptor {
+            label: Some("screenshot_buffer"),
+            size: padded_bytes_per_row as u64 * size.height as u64,
+            usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        captures.0.push(ScreenshotCapture {
+            request: ScreenshotRequest {
+                supersampling,
+                ..request
+            },
+            texture,
+            buffer,
+            width: size.width,
+            height: size.height,
+            padded_bytes_per_row,
+            format,
+        });
+    }
+}
+
+fn padded_bytes_per_row(width: u32) -> u32 {
+    let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+    let bytes_per_row = width * 4;
+    (bytes_per_row + align - 1) / align * align
+}
+
+/// Copies the rendered screenshots into their buffers after the main pass
+struct ScreenshotNode;
+
+impl Node for ScreenshotNode {
+    fn run(
+        &self,
+        _graph: &mut RenderGraphContext,
+        render_context: &mut RenderContext,
+        world: &World,
+    ) -> Result<(), NodeRunError> {
+        let captures = world.get_resource::<ScreenshotCaptures>().unwrap();
+        for capture in captures.0.iter() {
+            render_context.command_encoder.copy_texture_to_buffer(
+                ImageCopyTexture {
+                    texture: &capture.texture,
+                    mip_level: 0,
+                    origin: Origin3d::ZERO,
+                    aspect: TextureAspect::All,
+                },
+                ImageCopyBuffer {
+                    buffer: &capture.buffer,
+                    layout: ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: NonZeroU32::new(capture.padded_bytes_per_row),
+                        rows_per_image: None,
+                    },
+                },
+                Extent3d {
+                    width: capture.width,
+                    height: capture.height,
+                    depth_or_array_layers: 1,
+                },
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// The copies have been submitted, hand them over to the main world
+fn finish_screenshot_captures(
+    mut captures: ResMut<ScreenshotCaptures>,
+    captured: Res<CapturedScreenshots>,
+) {
+    if !captures.0.is_empty() {
+        captured.finished.lock().unwrap().append(&mut captures.0);
+    }
+}
+
+fn save_screenshots(
+    captured: Res<CapturedScreenshots>,
+    render_device: Res<RenderDevice>,
+    task_pool: Res<IoTaskPool>,
+) {
+    let finished = std::mem::take(&mut *captured.finished.lock().unwrap());
+    for capture in finished {
+        let in_flight = captured.in_flight.clone();
+        let render_device = render_device.clone();
+        task_pool
+            .spawn(async move {
+                let file_name = capture.request.file_name.clone();
+                match capture.save(&render_device).await {
+                    Ok(()) => info!("Saved screenshot {}", file_name),
+                    Err(e) => warn!("Failed to save screenshot {}: {}", file_name, e),
+                }
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+            })
+            .detach();
+    }
+}
+
+impl ScreenshotCapture {
+    async fn save(self, render_device: &RenderDevice) -> anyhow::Result<()> {
+        let slice = self.buffer.slice(..);
+        let mapped = slice.map_async(MapMode::Read);
+        // reading the buffer back only makes progress while the device is polled, the browser does this for us
+        if cfg!(not(target_arch = "wasm32")) {
+            render_device.poll(wgpu::Maintain::Wait);
+        }
+        mapped.await?;
+        let mut pixels = {
+            let padded = slice.get_mapped_range();
+            padded
+                .chunks(self.padded_bytes_per_row as usize)
+                .flat_map(|row| &row[..self.width as usize * 4])
+                .copied()
+                .collect::<Vec<u8>>()
+        };
+        self.buffer.unmap();
+
+        if matches!(
+            self.format,
+            TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb
+        ) {
+            for pixel in pixels.chunks_mut(4) {
+                pixel.swap(0, 2);
+            }
+        }
+        // the clear color's alpha doesn't mean anything on screen
+        for pixel in pixels.chunks_mut(4) {
+            pixel[3] = u8::MAX;
+        }
+
+        let supersampling = self.request.supersampling;
+        let (pixels, width, height) = if supersampling > 1 {
+            downsample(&pixels, self.width, self.height, supersampling)
+        } else {
+            (pixels, self.width, self.height)
+        };
+
+        let mut png = Vec::new();
+        image::codecs::png::PngEncoder::new(&mut png).encode(
+            &pixels,
+            width,
+            height,
+            image::ColorType::Rgba8,
+        )?;
+        save_png(&self.request.file_name, &png)
+    }
+}
+
+/// Average each `factor` x `factor` block of RGBA pixels into one.
+/// This averages the sRGB values directly, which darkens edges slightly but is close enough for anti-aliasing.
+fn downsample(pixels: &[u8], width: u32, height: u32, factor: u32) -> (Vec<u8>, u32, u32) {
+    let (out_width, out_height) = (width / factor, height / factor);
+    let mut out = Vec::with_capacity((out_width * out_height * 4) as usize);
+    for y in 0..out_height {
+        for x in 0..out_width {
+            let mut sum = [0u32; 4];
+            for sample_y in y * factor..(y + 1) * factor {
+                for sample_x in x * factor..(x + 1) * factor {
+                    let i = ((sample_y * width + sample_x) * 4) as usize;
+                    for (channel, sum) in sum.iter_mut().enumerate() {
+                        *sum += pixels[i + channel] as u32;
+                    }
+                }
+            }
+            out.extend(sum.iter().map(|sum| (sum / (factor * factor)) as u8));
+        }
+    }
+
+    (out, out_width, out_height)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn save_png(file_name: &str, png: &[u8]) -> anyhow::Result<()> {
+    std::fs::write(file_name, png)?;
+    Ok(())
+}
+
+/// Hand the PNG to the browser as a download
+#[cfg(target_arch = "wasm32")]
+fn save_png(file_name: &str, png: &[u8]) -> anyhow::Result<()> {
+    use wasm_bindgen::JsCast;
+
+    let document = web_sys::window()
+        .and_then(|window| window.document())
+        .ok_or_else(|| anyhow::anyhow!("There is no browser document"))?;
+
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(png));
+    let blob = web_sys::Blob::new_with_u8_array_sequence_and_options(
+        &parts,
+        web_sys::BlobPropertyBag::new().type_("image/png"),
+    )
+    .map_err(|e| anyhow::anyhow!("Failed to create the image: {:?}", e))?;
+    let url = web_sys::Url::create_object_url_with_blob(&blob)
+        .map_err(|e| anyhow::anyhow!("Failed to create a link to the image: {:?}", e))?;
+
+    let link = document
+        .create_element("a")
+        .map_err(|e| anyhow::anyhow!("Failed to create a download link: {:?}", e))?
+        .dyn_into::<web_sys::HtmlAnchorElement>()
+        .map_err(|e| anyhow::anyhow!("Failed to create a download link: {:?}", e))?;
+    link.set_href(&url);
+    link.set_download(file_name);
+    link.click();
+
+    web_sys::Url::revoke_object_url(&url)
+        .map_err(|e| anyhow::anyhow!("Failed to clean up the image link: {:?}", e))
+}
+
+/// The current UTC time like `2022-03-04_15-30-00`
+#[cfg(not(target_arch = "wasm32"))]
+fn timestamp() -> String {
+    let seconds = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs()) as i64;
+    let (days, time) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+
+    // days since the epoch to a date, from http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+    format!(
+        "{:04}-{:02}-{:02}_{:02}-{:02}-{:02}",
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+/// The current UTC time like `2022-03-04_15-30-00`
+#[cfg(target_arch = "wasm32")]
+fn timestamp() -> String {
+    let iso: String = js_sys::Date::new_0().to_iso_string().into();
+    // 2022-03-04T15:30:00.000Z
+    iso.get(..19)
+        .unwrap_or(&iso)
+        .replace('T', "_")
+        .replace(':', "-")
+}
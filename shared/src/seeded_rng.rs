@@ -0,0 +1,93 @@
+//! Reproducible randomness.
+//! Every run has a seed, logged at startup, and launching with `--seed 42` (or `?seed=42` on the web) repeats that run.
+//! Systems each take their own [`SeededRng::stream`] so adding randomness to one doesn't change what the others get.
+
+use bevy::prelude::*;
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::launch_config::LaunchConfig;
+
+/// The generator [`SeededRng::stream`] hands out, it gives the same numbers on every platform
+pub type RngStream = ChaCha8Rng;
+
+/// Adds [`SeededRng`], seeded from the [`LaunchConfig`]'s `seed` or randomly if there isn't one
+pub struct SeededRngPlugin;
+
+impl Plugin for SeededRngPlugin {
+    fn build(&self, app: &mut App) {
+        let seed = app
+            .world
+            .get_resource_mut::<LaunchConfig>()
+            .and_then(|mut config| config.parse::<u64>("seed"))
+            .unwrap_or_else(rand::random);
+        app.insert_resource(SeededRng::new(seed))
+            .add_startup_system(log_seed);
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SeededRng {
+    seed: u64,
+}
+
+impl SeededRng {
+    pub fn new(seed: u64) -> Self {
+        SeededRng { seed }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// A generator for `name`, independent of every other name's and the same every time for the same seed.
+    /// Systems that want new numbers every frame should keep theirs in a [`Local`] rather than asking again.
+    pub fn stream(&self, name: &str) -> RngStream {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.seed);
+        rng.set_stream(stream_id(name));
+        rng
+    }
+}
+
+/// 64 bit FNV-1a, unlike std's hashers it's guaranteed to stay the same between Rust versions
+fn stream_id(name: &str) -> u64 {
+    name.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+fn log_seed(rng: Res<SeededRng>) {
+    info!(
+        "Random seed {}, launch with seed={} to repeat this run",
+        rng.seed, rng.seed
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::Rng;
+
+    use super::*;
+
+    #[test]
+    fn stream_ids_match_fnv_1a() {
+        // published test vectors, if these change every seeded run changes with them
+        assert_eq!(stream_id(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(stream_id("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(stream_id("foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn stream_ids_are_stable() {
+        assert_eq!(stream_id("boids::setup"), 0x1a14_b7dc_86b4_ec29);
+        assert_eq!(stream_id("boids::grid"), 0xe6c5_c7ef_1476_191c);
+    }
+
+    #[test]
+    fn streams_repeat_for_the_same_seed() {
+        let first = SeededRng::new(42).stream("test").gen::<u64>();
+        assert_eq!(SeededRng::new(42).stream("test").gen::<u64>(), first);
+        assert_ne!(SeededRng::new(42).stream("other").gen::<u64>(), first);
+        assert_ne!(SeededRng::new(43).stream("test").gen::<u64>(), first);
+    }
+}
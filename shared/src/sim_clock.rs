@@ -0,0 +1,155 @@
+use bevy::core::CoreSystem;
+use bevy::prelude::*;
+
+/// Keep a [`SimClock`] for the art to animate by instead of [`Time`].
+/// Space pauses and resumes it, `.` steps it while paused and `[`/`]` halve and double its speed.
+pub struct SimClockPlugin;
+
+impl Plugin for SimClockPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SimClock>()
+            .init_resource::<SimClockSettings>()
+            .add_system_to_stage(
+                CoreStage::First,
+                update_sim_clock
+                    .label(SimClockSystem)
+                    .after(CoreSystem::Time),
+            )
+            .add_system(sim_clock_input);
+    }
+}
+
+/// Advances the [`SimClock`], anything that pauses or steps it for this frame should run before this
+#[derive(SystemLabel, Clone, Hash, Debug, PartialEq, Eq)]
+pub struct SimClockSystem;
+
+/// The time the art sees.
+/// It follows the real time unless it's given a fixed step, then every frame advances it by exactly that much,
+/// however long the frame really took.
+#[derive(Debug)]
+pub struct SimClock {
+    /// Seconds to advance every frame instead of the real frame time
+    pub fixed_step: Option<f32>,
+    pub paused: bool,
+    /// How many times faster than real time the clock runs
+    pub speed: f32,
+    /// Advance one step on the next frame even though the clock is paused
+    step_requested: bool,
+    delta_seconds: f32,
+    seconds_since_startup: f64,
+}
+
+impl Default for SimClock {
+    fn default() -> Self {
+        SimClock {
+            fixed_step: None,
+            paused: false,
+            speed: 1.0,
+            step_requested: false,
+            delta_seconds: 0.0,
+            seconds_since_startup: 0.0,
+        }
+    }
+}
+
+impl SimClock {
+    /// How far the clock moved this frame
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta_seconds
+    }
+
+    pub fn seconds_since_startup(&self) -> f64 {
+        self.seconds_since_startup
+    }
+
+    /// Start counting from 0 again
+    pub fn reset(&mut self) {
+        self.delta_seconds = 0.0;
+        self.seconds_since_startup = 0.0;
+    }
+
+    /// Pause the clock and move it forward by one step on the next frame
+    pub fn step(&mut self) {
+        self.paused = true;
+        self.step_requested = true;
+    }
+}
+
+/// How far things that aren't on the clock, like the camera, should move this frame.
+/// That's the real frame time, or the clock's fixed step when it has one so exports come out the same every time.
+/// A fixed step clock that didn't move this frame holds them still too, that's how an export waits for frames to save.
+pub fn frame_seconds(time: &Time, clock: Option<&SimClock>) -> f32 {
+    match clock.map(|clock| (clock.fixed_step, clock.delta_seconds)) {
+        Some((Some(fixed_step), delta_seconds)) if delta_seconds > 0.0 => fixed_step,
+        Some((Some(_), _)) => 0.0,
+        _ => time.delta_seconds(),
+    }
+}
+
+pub struct SimClockSettings {
+    pub pause_key: KeyCode,
+    pub step_key: KeyCode,
+    pub slower_key: KeyCode,
+    pub faster_key: KeyCode,
+    /// How far a single step moves the clock when it doesn't have a fixed step
+    pub step_seconds: f32,
+    pub min_speed: f32,
+    pub max_speed: f32,
+}
+
+impl Default for SimClockSettings {
+    fn default() -> Self {
+        SimClockSettings {
+            pause_key: KeyCode::Space,
+            step_key: KeyCode::Period,
+            slower_key: KeyCode::LBracket,
+            faster_key: KeyCode::RBracket,
+            step_seconds: 1.0 / 60.0,
+            min_speed: 1.0 / 64.0,
+            max_speed: 64.0,
+        }
+    }
+}
+
+fn update_sim_clock(time: Res<Time>, settings: Res<SimClockSettings>, mut clock: ResMut<SimClock>) {
+    let delta_seconds = if clock.paused {
+        if clock.step_requested {
+            clock.step_requested = false;
+            // a step is the same however fast the clock is running, so it can be used to compare frames
+            clock.fixed_step.unwrap_or(settings.step_seconds)
+        } else {
+            0.0
+        }
+    } else {
+        clock.fixed_step.unwrap_or_else(|| time.delta_seconds()) * clock.speed
+    };
+    clock.delta_seconds = delta_seconds;
+    clock.seconds_since_startup += delta_seconds as f64;
+}
+
+fn sim_clock_input(
+    settings: Res<SimClockSettings>,
+    input_keyboard: Res<Input<KeyCode>>,
+    mut clock: ResMut<SimClock>,
+) {
+    if input_keyboard.just_pressed(settings.pause_key) {
+        clock.paused = !clock.paused;
+        info!(
+            "{} the clock",
+            if clock.paused { "Paused" } else { "Resumed" }
+        );
+    }
+    if input_keyboard.just_pressed(settings.step_key) {
+        clock.step();
+    }
+
+    let speed = if input_keyboard.just_pressed(settings.slower_key) {
+        clock.speed / 2.0
+    } else if input_keyboard.just_pressed(settings.faster_key) {
+        clock.speed * 2.0
+    } else {
+        return;
+    };
+    clock.speed = speed.clamp(settings.min_speed, settings.max_speed);
+    info!("Running the clock at {}x", clock.speed);
+}
@@ -0,0 +1,42 @@
+//! Persist small text files between sessions.
+//! Natively they're written next to wherever the project was run from, on wasm they go in `localStorage`.
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn save(name: &str, contents: &str) -> anyhow::Result<()> {
+    std::fs::write(name, contents)?;
+    Ok(())
+}
+
+/// Load a previously saved file, `None` if nothing has been saved under `name` yet
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load(name: &str) -> anyhow::Result<Option<String>> {
+    match std::fs::read_to_string(name) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn save(name: &str, contents: &str) -> anyhow::Result<()> {
+    local_storage()?
+        .set_item(name, contents)
+        .map_err(|e| anyhow::anyhow!("Failed to write {} to localStorage: {:?}", name, e))
+}
+
+/// Load a previously saved file, `None` if nothing has been saved under `name` yet
+#[cfg(target_arch = "wasm32")]
+pub fn load(name: &str) -> anyhow::Result<Option<String>> {
+    local_storage()?
+        .get_item(name)
+        .map_err(|e| anyhow::anyhow!("Failed to read {} from localStorage: {:?}", name, e))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> anyhow::Result<web_sys::Storage> {
+    web_sys::window()
+        .ok_or_else(|| anyhow::anyhow!("There is no browser window"))?
+        .local_storage()
+        .map_err(|e| anyhow::anyhow!("Failed to access localStorage: {:?}", e))?
+        .ok_or_else(|| anyhow::anyhow!("localStorage is unavailable"))
+}
@@ -0,0 +1,329 @@
+use std::collections::BTreeMap;
+use std::f32::consts::{PI, TAU};
+use std::fmt;
+
+use bevy::prelude::*;
+
+use crate::launch_config::LaunchConfig;
+use crate::pan_orbit_camera::constraints::PanOrbitConstraints;
+use crate::pan_orbit_camera::{OrbitView, PanOrbitCamera, PanOrbitState, PanOrbitSystem};
+
+/// How long the camera has to sit still before the page's link is updated
+#[cfg(target_arch = "wasm32")]
+const DEBOUNCE_SECONDS: f32 = 0.5;
+
+/// Keep the camera view and the changed [`crate::params::Params`] in a link that can be shared.
+/// On wasm it's the page's `#` hash, natively it's passed with `--view` and logged with [`ViewLinkSettings::copy_key`].
+/// The view in the link is restored when the cameras are spawned, and its params are set like the [`LaunchConfig`]'s,
+/// so add this before any params are registered.
+pub struct ViewLinkPlugin;
+
+impl Plugin for ViewLinkPlugin {
+    fn build(&self, app: &mut App) {
+        let link = match initial_link(&mut app.world) {
+            Some(link) => ViewLink::parse(&link),
+            None => ViewLink::default(),
+        };
+        // settings passed in directly win over the link's
+        if let Some(mut config) = app.world.get_resource_mut::<LaunchConfig>() {
+            for (key, value) in link.params.iter() {
+                config.insert_missing(key, value.clone());
+            }
+        }
+
+        app.insert_resource(link)
+            .init_resource::<ViewLinkSettings>()
+            .add_system(restore_view_link.before(PanOrbitSystem::Camera))
+            .add_system(
+                update_view_link
+                    .label(ViewLinkSystem)
+                    .after(PanOrbitSystem::Camera),
+            )
+            .add_system(copy_view_link.after(ViewLinkSystem));
+        #[cfg(target_arch = "wasm32")]
+        app.add_system(write_view_link.after(ViewLinkSystem));
+    }
+}
+
+/// Updates the [`ViewLink`], anything that reads it should run after this
+#[derive(SystemLabel, Clone, Hash, Debug, PartialEq, Eq)]
+pub struct ViewLinkSystem;
+
+pub struct ViewLinkSettings {
+    /// Log the link so it can be copied
+    pub copy_key: KeyCode,
+}
+
+impl Default for ViewLinkSettings {
+    fn default() -> Self {
+        ViewLinkSettings {
+            copy_key: KeyCode::L,
+        }
+    }
+}
+
+/// The state in a shareable link, formatted like `view=x,y,z,radius,yaw,pitch&name=value`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ViewLink {
+    pub view: Option<OrbitView>,
+    /// The params fields that aren't at their defaults, kept up to date by [`crate::params::ParamsPlugin`]
+    pub params: BTreeMap<String, String>,
+}
+
+impl ViewLink {
+    /// Read whatever is valid out of `link`, warning about the rest
+    pub fn parse(link: &str) -> Self {
+        let mut parsed = ViewLink::default();
+        for pair in link.trim_start_matches('#').split('&') {
+            if pair.is_empty() {
+                continue;
+            }
+
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            if key == "view" {
+                parsed.view = parse_view(value);
+                if parsed.view.is_none() {
+                    warn!("Ignoring invalid view in link: {}", value);
+                }
+            } else {
+                parsed
+                    .params
+                    .insert(decode_component(key), decode_component(value));
+            }
+        }
+
+        parsed
+    }
+}
+
+impl fmt::Display for ViewLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut separator = "";
+        if let Some(view) = &self.view {
+            write!(
+                f,
+                "view={},{},{},{},{},{}",
+                view.focus.x, view.focus.y, view.focus.z, view.radius, view.yaw, view.pitch
+            )?;
+            separator = "&";
+        }
+        for (key, value) in self.params.iter() {
+            write!(
+                f,
+                "{}{}={}",
+                separator,
+                encode_component(key),
+                encode_component(value)
+            )?;
+            separator = "&";
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_view(value: &str) -> Option<OrbitView> {
+    let values = value
+        .split(',')
+        .map(|value| value.parse::<f32>().ok().filter(|value| value.is_finite()))
+        .collect::<Option<Vec<_>>>()?;
+
+    match values[..] {
+        [x, y, z, radius, yaw, pitch] if radius > 0.0 => Some(OrbitView {
+            focus: Vec3::new(x, y, z),
+            radius,
+            yaw,
+            pitch,
+        }),
+        _ => None,
+    }
+}
+
+/// URL encode anything that could be mistaken for part of the link
+fn encode_component(component: &str) -> String {
+    let mut encoded = String::with_capacity(component.len());
+    for byte in component.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b',' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Undo the URL encoding of a link or query string component, `+` is a space and `%XX` is a byte
+pub(crate) fn decode_component(component: &str) -> String {
+    let bytes = component.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' => match component
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+            {
+                Some(byte) => {
+                    decoded.push(byte);
+                    i += 2;
+                }
+                // a lone `%` is left as it is
+                None => decoded.push(b'%'),
+            },
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Keep links short and stop them changing over differences too small to see
+fn rounded(view: OrbitView) -> OrbitView {
+    let round = |value: f32| (value * 1000.0).round() / 1000.0;
+    OrbitView {
+        focus: Vec3::new(
+            round(view.focus.x),
+            round(view.focus.y),
+            round(view.focus.z),
+        ),
+        radius: round(view.radius),
+        // the turntable keeps winding the yaw up
+        yaw: round((view.yaw + PI).rem_euclid(TAU) - PI),
+        pitch: round(view.pitch),
+    }
+}
+
+/// Jump new cameras to the view from the link
+#[allow(clippy::type_complexity)]
+fn restore_view_link(
+    link: Res<ViewLink>,
+    mut query: Query<
+        (
+            &mut PanOrbitCamera,
+            &mut PanOrbitState,
+            Option<&PanOrbitConstraints>,
+        ),
+        Added<PanOrbitState>,
+    >,
+) {
+    if let Some(view) = link.view {
+        for (mut pan_orbit, mut state, constraints) in query.iter_mut() {
+            state.snap(&mut pan_orbit, view, constraints);
+        }
+    }
+}
+
+/// Copy the camera into the link
+fn update_view_link(mut link: ResMut<ViewLink>, query: Query<&PanOrbitCamera>) {
+    if let Some(pan_orbit) = query.iter().find(|pan_orbit| pan_orbit.enabled) {
+        let view = rounded(pan_orbit.view());
+        // only compare through `Deref` so the link isn't marked as changed every frame
+        if link.view.map(rounded) != Some(view) {
+            link.view = Some(view);
+        }
+    }
+}
+
+fn copy_view_link(
+    input_keyboard: Res<Input<KeyCode>>,
+    settings: Res<ViewLinkSettings>,
+    link: Res<ViewLink>,
+) {
+    if input_keyboard.just_pressed(settings.copy_key) {
+        log_link(&link);
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+#[derive(Default)]
+struct ViewLinkDebounce {
+    dirty: bool,
+    since_change: f32,
+}
+
+/// Put the link in the page's hash once it stops changing
+#[cfg(target_arch = "wasm32")]
+fn write_view_link(time: Res<Time>, link: Res<ViewLink>, mut debounce: Local<ViewLinkDebounce>) {
+    if link.is_changed() {
+        debounce.dirty = true;
+        debounce.since_change = 0.0;
+    } else {
+        debounce.since_change += time.delta_seconds();
+    }
+
+    if debounce.dirty && debounce.since_change >= DEBOUNCE_SECONDS {
+        debounce.dirty = false;
+        if let Err(e) = write_link(&link) {
+            warn!("Failed to update the view link: {}", e);
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn initial_link(world: &mut World) -> Option<String> {
+    let mut config = world.get_resource_or_insert_with(LaunchConfig::from_environment);
+    config.get("view").map(|link| link.to_string())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn log_link(link: &ViewLink) {
+    info!("Come back to this view with --view \"{}\"", link);
+}
+
+#[cfg(target_arch = "wasm32")]
+fn initial_link(_world: &mut World) -> Option<String> {
+    let hash = web_sys::window()?.location().hash().ok()?;
+    Some(hash.trim_start_matches('#').to_string()).filter(|hash| !hash.is_empty())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn log_link(link: &ViewLink) {
+    info!("Come back to this view with #{}", link);
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_link(link: &ViewLink) -> anyhow::Result<()> {
+    let window = web_sys::window().ok_or_else(|| anyhow::anyhow!("There is no browser window"))?;
+    // replace instead of push so the back button doesn't step through every camera move
+    window
+        .history()
+        .map_err(|e| anyhow::anyhow!("Failed to access the history: {:?}", e))?
+        .replace_state_with_url(
+            &wasm_bindgen::JsValue::NULL,
+            "",
+            Some(&format!("#{}", link)),
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to set the hash: {:?}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_round_trip() {
+        let mut link = ViewLink {
+            view: Some(OrbitView {
+                focus: Vec3::new(1.0, -2.0, 0.5),
+                radius: 12.0,
+                yaw: 0.25,
+                pitch: -1.0,
+            }),
+            ..Default::default()
+        };
+        link.params.insert("count".to_string(), "2000".to_string());
+        link.params
+            .insert("title".to_string(), "a&b=c 100%".to_string());
+
+        assert_eq!(ViewLink::parse(&link.to_string()), link);
+    }
+
+    #[test]
+    fn invalid_views_are_dropped() {
+        let link = ViewLink::parse("#view=1,2,3,0,0,0&count=5");
+        assert_eq!(link.view, None);
+        assert_eq!(link.params.get("count").map(String::as_str), Some("5"));
+    }
+}